@@ -0,0 +1,344 @@
+//! Command-line interface for comparing two event logs with the permutation test
+//! or the bootstrap test.
+//!
+//! The exit code reflects the outcome of the test, using the p-value `(b+1)/(m+1)`:
+//! `0` if the difference is not significant at the given alpha, `1` if it is
+//! significant, and `2` on errors.
+
+use std::{fmt::Debug, fs::File, hash::Hash, path::PathBuf, process::ExitCode};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use pcomprs::{
+    binning::kmeans_binner::KMeansArgs,
+    comparators::{
+        bootstrap::{
+            bootstrap_comparator::BootstrapTestComparator,
+            control_flow::ControlFlowBootstrapComparator,
+            timed_levenshtein::TimedLevenshteinBootstrapComparator,
+        },
+        common::{
+            preparation::ensure_start_timestamp_key,
+            result::{ComparisonResult, ResultExportError},
+            with_progress::WithProgress,
+        },
+        permutation_test::{
+            control_flow::ControlFlowPermutationComparator,
+            permutation_test_comparator::PermutationTestComparator,
+            timed_levenshtein::TimedLevenshteinPermutationComparator,
+        },
+    },
+    report::ComparisonReport,
+    stats::{
+        pvalue::{ExceedanceComparison, PValueOptions},
+        sequential::SequentialOptions,
+    },
+    utils::progress::{IndicatifReporter, ProgressReporter, SilentReporter},
+};
+use process_mining::{import_xes_file, EventLog, XESImportOptions};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "pcomp",
+    version,
+    about = "Process hypothesis testing: compare the behavior of two event logs.",
+    after_help = "Exit codes: 0 if the difference is not significant, 1 if it is significant, 2 on errors."
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Compare the event logs using the permutation test.
+    Permutation {
+        #[command(flatten)]
+        common: CommonArgs,
+    },
+    /// Compare the event logs using the bootstrap test (P-P-UP).
+    Bootstrap {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// The size of each bootstrap sample. Defaults to the number of cases in
+        /// the first event log.
+        #[arg(long)]
+        resample_size: Option<usize>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ComparatorKind {
+    /// Compare the control flow (activity sequences).
+    ControlFlow,
+    /// Compare the control flow including binned service times.
+    Timed,
+}
+
+#[derive(Debug, Args)]
+struct CommonArgs {
+    /// The first event log (XES or XES.gz).
+    log_1: PathBuf,
+    /// The second event log (XES or XES.gz).
+    log_2: PathBuf,
+    /// The comparator to use.
+    #[arg(short, long, value_enum, default_value_t = ComparatorKind::ControlFlow)]
+    comparator: ComparatorKind,
+    /// The number of EMDs to compute for the test distribution. With `--sequential`,
+    /// the maximum number.
+    #[arg(short, long, default_value_t = 10_000)]
+    distribution_size: usize,
+    /// Stop computing the test distribution once the p-value is confidently above
+    /// or below alpha.
+    #[arg(long)]
+    sequential: bool,
+    /// Count EMDs equal to the observed EMD as exceeding it (`>=` instead of `>`).
+    #[arg(long)]
+    ties_exceed: bool,
+    /// The seed for the random sampling.
+    #[arg(short, long)]
+    seed: Option<u64>,
+    /// The significance level.
+    #[arg(short, long, default_value_t = 0.05)]
+    alpha: f64,
+    /// The number of bins (K-Means++ clusters) for service times (timed comparator).
+    #[arg(long, default_value_t = 3)]
+    bins: usize,
+    /// The maximum number of K-Means++ iterations (timed comparator).
+    #[arg(long, default_value_t = 100)]
+    binning_max_iter: usize,
+    /// The seed for the K-Means++ initialization (timed comparator).
+    #[arg(long)]
+    binning_seed: Option<u64>,
+    /// Write the result (p-value, observed EMD, test distribution and metadata)
+    /// as JSON to this file.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Write the test distribution as CSV to this file.
+    #[arg(long)]
+    distribution_csv: Option<PathBuf>,
+    /// Write a self-contained HTML report to this file.
+    #[arg(long)]
+    report: Option<PathBuf>,
+    /// Do not show progress bars.
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+impl CommonArgs {
+    fn binner_args(&self) -> KMeansArgs {
+        KMeansArgs::new(self.bins, self.binning_max_iter, self.binning_seed)
+    }
+
+    fn exceedance_comparison(&self) -> ExceedanceComparison {
+        if self.ties_exceed {
+            ExceedanceComparison::GreaterOrEqual
+        } else {
+            ExceedanceComparison::Greater
+        }
+    }
+
+    fn pvalue_options(&self) -> PValueOptions {
+        PValueOptions::default().with_comparison(self.exceedance_comparison())
+    }
+
+    fn sequential_options(&self) -> SequentialOptions {
+        SequentialOptions::new(self.alpha).with_exceedance_comparison(self.exceedance_comparison())
+    }
+
+    fn progress_reporter(&self) -> Box<dyn ProgressReporter> {
+        if self.quiet {
+            Box::new(SilentReporter)
+        } else {
+            Box::new(IndicatifReporter)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli.command) {
+        Ok(significant) => ExitCode::from(significant as u8),
+        Err(message) => {
+            eprintln!("Error: {message}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Run the comparison. Returns whether the difference is significant.
+fn run(command: Command) -> Result<bool, String> {
+    match command {
+        Command::Permutation { common } => {
+            let (log_1, log_2) = import_logs(&common)?;
+            match common.comparator {
+                ComparatorKind::ControlFlow => {
+                    run_permutation(ControlFlowPermutationComparator, &log_1, &log_2, &common)
+                }
+                ComparatorKind::Timed => run_permutation(
+                    TimedLevenshteinPermutationComparator::new(common.binner_args()),
+                    &log_1,
+                    &log_2,
+                    &common,
+                ),
+            }
+        }
+        Command::Bootstrap {
+            common,
+            resample_size,
+        } => {
+            let (log_1, log_2) = import_logs(&common)?;
+            let resample_size = resample_size.unwrap_or(log_1.traces.len());
+            match common.comparator {
+                ComparatorKind::ControlFlow => run_bootstrap(
+                    ControlFlowBootstrapComparator,
+                    &log_1,
+                    &log_2,
+                    resample_size,
+                    &common,
+                ),
+                ComparatorKind::Timed => run_bootstrap(
+                    TimedLevenshteinBootstrapComparator::new(common.binner_args()),
+                    &log_1,
+                    &log_2,
+                    resample_size,
+                    &common,
+                ),
+            }
+        }
+    }
+}
+
+fn import_logs(args: &CommonArgs) -> Result<(EventLog, EventLog), String> {
+    let import = |path: &PathBuf| -> Result<EventLog, String> {
+        let mut log = import_xes_file(&path.to_string_lossy(), XESImportOptions::default())
+            .map_err(|err| format!("Failed to import {}: {err:?}", path.display()))?;
+        if let ComparatorKind::Timed = args.comparator {
+            ensure_start_timestamp_key(&mut log).map_err(|err| err.to_string())?;
+        }
+        Ok(log)
+    };
+    Ok((import(&args.log_1)?, import(&args.log_2)?))
+}
+
+fn run_permutation<T, C>(
+    comparator: C,
+    log_1: &EventLog,
+    log_2: &EventLog,
+    args: &CommonArgs,
+) -> Result<bool, String>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: PermutationTestComparator<T>,
+{
+    let comparator = WithProgress::new(comparator, args.progress_reporter());
+    let result = if args.sequential {
+        comparator.compare_sequential(
+            log_1,
+            log_2,
+            args.distribution_size,
+            &args.sequential_options(),
+            args.seed,
+        )
+    } else {
+        comparator.compare_with_options(
+            log_1,
+            log_2,
+            args.distribution_size,
+            &args.pvalue_options(),
+            args.seed,
+        )
+    }
+    .map_err(|err| err.to_string())?;
+    report(&result, args)
+}
+
+fn run_bootstrap<T, C>(
+    comparator: C,
+    log_1: &EventLog,
+    log_2: &EventLog,
+    resample_size: usize,
+    args: &CommonArgs,
+) -> Result<bool, String>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: BootstrapTestComparator<T>,
+{
+    let comparator = WithProgress::new(comparator, args.progress_reporter());
+    let result = if args.sequential {
+        comparator.compare_sequential(
+            log_1,
+            log_2,
+            resample_size,
+            args.distribution_size,
+            &args.sequential_options(),
+            args.seed,
+        )
+    } else {
+        comparator.compare_with_options(
+            log_1,
+            log_2,
+            resample_size,
+            args.distribution_size,
+            &args.pvalue_options(),
+            args.seed,
+        )
+    }
+    .map_err(|err| err.to_string())?;
+    report(&result, args)
+}
+
+/// Print the outcome of the comparison and write the requested output files.
+/// Returns whether the difference is significant.
+fn report(result: &impl ComparisonResult, args: &CommonArgs) -> Result<bool, String> {
+    let estimate = result.pvalue_estimate();
+    let significant = result.pvalue() < args.alpha;
+    println!("p-value: {} (b/m: {})", result.pvalue(), estimate.raw);
+    println!(
+        "{:.0}% confidence interval of the p-value: [{:.4}, {:.4}], standard error: {:.4}",
+        estimate.confidence * 100.0,
+        estimate.confidence_interval.0,
+        estimate.confidence_interval.1,
+        estimate.standard_error,
+    );
+    println!("Observed EMD: {}", result.logs_emd());
+    let effect_size = result.effect_size();
+    println!(
+        "Effect size: z-score {}, EMD - null median {:.4}, distance above noise {:.4}",
+        effect_size
+            .z_score
+            .map_or("n/a".to_string(), |z| format!("{z:.3}")),
+        effect_size.median_difference,
+        effect_size.distance_above_noise,
+    );
+    if let Some(summary) = result.sequential() {
+        println!(
+            "Samples used: {}/{}",
+            summary.samples_used, summary.max_samples
+        );
+    }
+    println!(
+        "The difference is {}significant at alpha = {}",
+        if significant { "" } else { "not " },
+        args.alpha
+    );
+
+    if let Some(path) = &args.output {
+        result
+            .write_json(path)
+            .map_err(|err| format!("Failed to write {}: {err}", path.display()))?;
+    }
+    if let Some(path) = &args.distribution_csv {
+        File::create(path)
+            .map_err(ResultExportError::from)
+            .and_then(|file| result.write_distribution_csv(file))
+            .map_err(|err| format!("Failed to write {}: {err}", path.display()))?;
+    }
+
+    if let Some(path) = &args.report {
+        ComparisonReport::new(result)
+            .write(path)
+            .map_err(|err| format!("Failed to write {}: {err}", path.display()))?;
+    }
+
+    Ok(significant)
+}
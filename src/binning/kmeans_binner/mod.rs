@@ -4,9 +4,10 @@ use super::Binner;
 // It is called kmeans, but uses the KMeans++ initializer, so it is KMeans++
 use _kmeans::kmeans;
 use itertools::Itertools;
+use serde::Serialize;
 
 /// Arguments for K-Means++ clustering.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct KMeansArgs {
     /// The number of clusters
     k: usize,
@@ -41,6 +42,11 @@ impl KMeansArgs {
         self.k = k;
         self
     }
+
+    /// The number of clusters, i.e., the number of bins.
+    pub fn num_bins(&self) -> usize {
+        self.k
+    }
 }
 
 /// Binning based on K-Means++ Clustering
@@ -33,6 +33,13 @@ where
         self.binners.get(label).unwrap().bin(data)
     }
 
+    /// The number of bins for a certain class (activity).
+    ///
+    /// Panics if the activity was not in the training data.
+    pub fn num_bins(&self, label: &str) -> usize {
+        self.binners.get(label).unwrap().num_bins()
+    }
+
     /// Create a [`BinnerManager`] from (key, value) pairs. For each unique key, a
     /// binner is created trained on the respective values.
     pub fn from_key_value_pairs(data: Vec<(String, U)>, binner_args: T::Args) -> Self {
@@ -1,28 +1,92 @@
-use std::{fmt::Debug, hash::Hash};
+use std::{fmt::Debug, hash::Hash, time::Instant};
 
-use itertools::Itertools;
-use ndarray::Array2;
+use ndarray::{Array1, Array2};
 use process_mining::EventLog;
 use rand::{
-    distributions::{Distribution, WeightedIndex},
+    distributions::{DistIter, Distribution, WeightedIndex},
     rngs::StdRng,
     SeedableRng,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    comparators::common::stochastic_language::StochasticLanguage,
+    comparators::common::{
+        result::{
+            short_type_name, ComparatorParameters, ComparisonMetadata, ComparisonResult,
+            CRATE_VERSION,
+        },
+        stochastic_language::StochasticLanguage,
+    },
+    distance::matrix::DistanceMatrix,
     emd::compute_emd,
-    utils::{attributes::attribute_error::AttributeResult, progress::build_progress_bar},
+    stats::{
+        effect_size::EffectSize,
+        pvalue::{PValueEstimate, PValueOptions},
+        sequential::{sequential_distribution, SamplingMode, SequentialOptions, SequentialSummary},
+    },
+    utils::{
+        attributes::attribute_error::AttributeResult,
+        nullable_float,
+        progress::{IndicatifReporter, ProgressReporter},
+    },
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BootstrapTestComparisonResult {
     /// The EMD measured between the two event logs.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
     pub logs_emd: f64,
     /// The EMDs computed in the bootstrapping phase.
+    #[serde(deserialize_with = "nullable_float::deserialize_vec")]
     pub bootstrap_emds: Vec<f64>,
-    /// The computed p-value
+    /// The p-value `(b + 1) / (m + 1)`, where `b` of the `m` EMDs in the test
+    /// distribution exceed the observed EMD. See `pvalue_estimate` for the raw
+    /// fraction `b / m` and the confidence interval.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
     pub pvalue: f64,
+    /// The p-value estimate including its Monte Carlo error.
+    #[serde(default)]
+    pub pvalue_estimate: PValueEstimate,
+    /// Effect sizes of the observed EMD relative to the test distribution. See
+    /// [`EffectSize`] for their interpretation.
+    #[serde(default)]
+    pub effect_size: EffectSize,
+    /// Information about how the result was obtained.
+    pub metadata: ComparisonMetadata,
+    /// The summary of the sequential test, if the comparison was run with
+    /// [`compare_sequential`](BootstrapTestComparator::compare_sequential).
+    #[serde(default)]
+    pub sequential: Option<SequentialSummary>,
+}
+
+impl ComparisonResult for BootstrapTestComparisonResult {
+    fn logs_emd(&self) -> f64 {
+        self.logs_emd
+    }
+
+    fn distribution(&self) -> &[f64] {
+        &self.bootstrap_emds
+    }
+
+    fn pvalue(&self) -> f64 {
+        self.pvalue
+    }
+
+    fn metadata(&self) -> &ComparisonMetadata {
+        &self.metadata
+    }
+
+    fn pvalue_estimate(&self) -> &PValueEstimate {
+        &self.pvalue_estimate
+    }
+
+    fn effect_size(&self) -> &EffectSize {
+        &self.effect_size
+    }
+
+    fn sequential(&self) -> Option<&SequentialSummary> {
+        self.sequential.as_ref()
+    }
 }
 
 /// The Bootstrap Method for Process Hypothesis Testing proposed in "Statistical
@@ -30,9 +94,11 @@ pub struct BootstrapTestComparisonResult {
 /// P-P-UP test).
 ///
 /// A bootstrap distribution is created by repeatedly computing the EMD between
-/// the first event log and a sample of itself (with replacement). Then, the p-value
-/// is computed as the fraction of bootstrap distribution values greater than the
-/// EMD between the two event logs.
+/// the first event log and a sample of itself (with replacement). The reported
+/// p-value is the estimate `(b + 1) / (m + 1)`, where `b` of the `m` bootstrap EMDs
+/// exceed the EMD between the two event logs. Whether equal EMDs count as exceeding
+/// it is configured by the [`PValueOptions`]. See [`PValueEstimate`] for the raw
+/// fraction `b / m` and its Clopper-Pearson confidence interval.
 pub trait BootstrapTestComparator<T>
 where
     T: Hash + Eq + Clone + Ord + Debug,
@@ -49,6 +115,26 @@ where
         log_2: &EventLog,
     ) -> AttributeResult<(Vec<T>, Vec<T>)>;
 
+    /// The name of the comparator recorded in the [`ComparisonMetadata`]. Defaults
+    /// to the unqualified type name.
+    fn name(&self) -> String {
+        short_type_name(std::any::type_name::<Self>())
+    }
+
+    /// The parameters of the comparator recorded in the [`ComparisonMetadata`].
+    fn parameters(&self) -> ComparatorParameters {
+        ComparatorParameters::new()
+    }
+
+    /// Where the progress of long-running computations (distance matrices, test
+    /// distributions) is reported. Defaults to progress bars on stderr; See
+    /// [`WithProgress`] to silence or redirect it.
+    ///
+    /// [`WithProgress`]: crate::comparators::common::with_progress::WithProgress
+    fn progress_reporter(&self) -> &dyn ProgressReporter {
+        &IndicatifReporter
+    }
+
     /// Compare two event logs.
     ///
     /// - Returns an `Err` if required attributes are not present on the events.
@@ -67,43 +153,80 @@ where
         distribution_size: usize,
         seed: Option<u64>,
     ) -> AttributeResult<BootstrapTestComparisonResult> {
-        let (behavior_1, behavior_2) = self.extract_representations(log_1, log_2)?;
-
-        let stoch_lang_1 = StochasticLanguage::from_items(behavior_1);
-        let stoch_lang_2 = StochasticLanguage::from_items(behavior_2);
-
-        let logs_emd = compute_emd(
-            stoch_lang_1.frequencies.clone(),
-            stoch_lang_2.frequencies.clone(),
-            &self.compute_distance_matrix(&stoch_lang_1.variants, &stoch_lang_2.variants),
+        self.compare_with_options(
+            log_1,
+            log_2,
+            resample_size,
+            distribution_size,
+            &PValueOptions::default(),
+            seed,
         )
-        .emd;
-
-        let bootstrap_emds =
-            self.bootstrap_emd_population(stoch_lang_1, resample_size, distribution_size, seed);
+    }
 
-        let pvalue = bootstrap_emds
-            .iter()
-            .filter(|emd| **emd > logs_emd)
-            .collect_vec()
-            .len() as f64
-            / distribution_size as f64;
+    /// Compare two event logs like [`compare`](BootstrapTestComparator::compare),
+    /// with options for computing the p-value, e.g., whether EMDs equal to the observed
+    /// EMD count as exceeding it.
+    fn compare_with_options(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+        resample_size: usize,
+        distribution_size: usize,
+        pvalue_options: &PValueOptions,
+        seed: Option<u64>,
+    ) -> AttributeResult<BootstrapTestComparisonResult> {
+        run_bootstrap_test(
+            self,
+            log_1,
+            log_2,
+            resample_size,
+            distribution_size,
+            SamplingMode::Fixed(pvalue_options),
+            seed,
+        )
+    }
 
-        Ok(BootstrapTestComparisonResult {
-            logs_emd,
-            bootstrap_emds,
-            pvalue,
-        })
+    /// Compare two event logs using a sequential test: Bootstrap samples are computed
+    /// until the p-value is confidently above or below the significance level (see
+    /// [`SequentialOptions`]), or `max_distribution_size` samples have been computed.
+    /// The p-value is computed from the samples actually used, which are reported in
+    /// [`BootstrapTestComparisonResult::sequential`].
+    ///
+    /// Returns an `Err` under the same conditions as [`compare`](BootstrapTestComparator::compare).
+    fn compare_sequential(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+        resample_size: usize,
+        max_distribution_size: usize,
+        options: &SequentialOptions,
+        seed: Option<u64>,
+    ) -> AttributeResult<BootstrapTestComparisonResult> {
+        run_bootstrap_test(
+            self,
+            log_1,
+            log_2,
+            resample_size,
+            max_distribution_size,
+            SamplingMode::Sequential(options),
+            seed,
+        )
     }
 
     /// Compute the distance matrix between two collections of variants using
     /// the [`cost`] function.
     ///
-    /// The output matrix has dimensions `(variants_1.len(), variants_2.len())`.
+    /// The output matrix has dimensions `(variants_1.len(), variants_2.len())`. The
+    /// progress is reported to `reporter`.
     ///
     /// [`cost`]: BootstrapTestComparator::cost
-    fn compute_distance_matrix(&self, variants_1: &[T], variants_2: &[T]) -> Array2<f64> {
-        let progress = build_progress_bar(
+    fn compute_distance_matrix(
+        &self,
+        variants_1: &[T],
+        variants_2: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Array2<f64> {
+        let progress = reporter.start(
             variants_1.len() as u64 * variants_2.len() as u64,
             format!(
                 "Computing distance matrix ({}x{})",
@@ -121,6 +244,30 @@ where
         dists
     }
 
+    /// The distance matrix between two collections of variants used for the tests.
+    /// Defaults to the dense matrix computed by [`compute_distance_matrix`]. For very
+    /// large sets of variants, see [`LazyComparator`] to only compute the distances
+    /// that are actually needed.
+    ///
+    /// The progress is reported to `reporter`, i.e., the [`progress_reporter`] of the
+    /// outermost comparator, so that wrapping comparators do not change where it is
+    /// reported.
+    ///
+    /// [`compute_distance_matrix`]: BootstrapTestComparator::compute_distance_matrix
+    /// [`LazyComparator`]: crate::comparators::common::lazy_comparator::LazyComparator
+    /// [`progress_reporter`]: BootstrapTestComparator::progress_reporter
+    fn distance_matrix<'a>(
+        &'a self,
+        variants_1: &[T],
+        variants_2: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Box<dyn DistanceMatrix + 'a>
+    where
+        T: 'a,
+    {
+        Box::new(self.compute_distance_matrix(variants_1, variants_2, reporter))
+    }
+
     /// Compute the bootstrap distribution by repeatedly taking samples of size
     /// `resample_size` from `reference_stochastic_language` with replacement,
     /// and computing the EMD to `reference_stochastic_language`.
@@ -138,12 +285,40 @@ where
         distribution_size: usize,
         seed: Option<u64>,
     ) -> Vec<f64> {
-        let distance_matrix = self.compute_distance_matrix(
+        let sampler = self.bootstrap_sampler(reference_stochastic_language, resample_size, seed);
+
+        let progress = self.progress_reporter().start(
+            distribution_size as u64,
+            "Computing bootstrap EMD distribution".into(),
+        );
+
+        let emds = sampler
+            .take(distribution_size)
+            .inspect(|_| progress.inc(1))
+            .collect();
+        progress.finish();
+        emds
+    }
+
+    /// Create an endless iterator over samples of the bootstrap distribution. See
+    /// [`bootstrap_emd_population`](BootstrapTestComparator::bootstrap_emd_population)
+    /// for the arguments.
+    fn bootstrap_sampler<'a>(
+        &'a self,
+        reference_stochastic_language: StochasticLanguage<T>,
+        resample_size: usize,
+        seed: Option<u64>,
+    ) -> BootstrapSampler<'a>
+    where
+        T: 'a,
+    {
+        let distance_matrix = self.distance_matrix(
             &reference_stochastic_language.variants,
             &reference_stochastic_language.variants,
+            self.progress_reporter(),
         );
 
-        let mut sampler = WeightedIndex::new(reference_stochastic_language.frequencies.clone())
+        let sampler = WeightedIndex::new(reference_stochastic_language.frequencies.clone())
             .unwrap()
             .sample_iter(if let Some(s) = seed {
                 StdRng::seed_from_u64(s)
@@ -151,28 +326,135 @@ where
                 StdRng::from_entropy()
             });
 
-        let progress = build_progress_bar(
-            distribution_size as u64,
-            "Computing permutation EMD distribution".into(),
-        );
+        BootstrapSampler {
+            distance_matrix,
+            reference_indices: (0..reference_stochastic_language.variants.len()).collect(),
+            reference_frequencies: reference_stochastic_language.frequencies,
+            resample_size,
+            sampler,
+        }
+    }
+}
 
-        let emds = (0..distribution_size)
-            .map(|_| {
-                let sample_indices: Vec<usize> = sampler.by_ref().take(resample_size).collect();
-                let sample_stochastic_language = StochasticLanguage::from_items(sample_indices);
-                let projected_costs =
-                    distance_matrix.select(ndarray::Axis(0), &sample_stochastic_language.variants);
-                let emd = compute_emd(
-                    sample_stochastic_language.frequencies,
-                    reference_stochastic_language.frequencies.clone(),
-                    &projected_costs,
-                )
-                .emd;
-                progress.inc(1);
-                emd
-            })
-            .collect();
-        progress.finish();
-        emds
+/// An endless iterator over samples of the bootstrap distribution, created using
+/// [`BootstrapTestComparator::bootstrap_sampler`].
+#[derive(Debug)]
+pub struct BootstrapSampler<'a> {
+    distance_matrix: Box<dyn DistanceMatrix + 'a>,
+    reference_indices: Vec<usize>,
+    reference_frequencies: Array1<f64>,
+    resample_size: usize,
+    sampler: DistIter<WeightedIndex<f64>, StdRng, usize>,
+}
+
+impl Iterator for BootstrapSampler<'_> {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<f64> {
+        let sample_indices: Vec<usize> = self.sampler.by_ref().take(self.resample_size).collect();
+        let sample_stochastic_language = StochasticLanguage::from_items(sample_indices);
+        let projected_costs = self.distance_matrix.select(
+            &sample_stochastic_language.variants,
+            &self.reference_indices,
+        );
+        Some(
+            compute_emd(
+                sample_stochastic_language.frequencies,
+                self.reference_frequencies.clone(),
+                &projected_costs,
+            )
+            .emd,
+        )
     }
 }
+
+/// Run the bootstrap test, either with a fixed distribution size or sequentially.
+fn run_bootstrap_test<T, C>(
+    comparator: &C,
+    log_1: &EventLog,
+    log_2: &EventLog,
+    resample_size: usize,
+    distribution_size: usize,
+    mode: SamplingMode,
+    seed: Option<u64>,
+) -> AttributeResult<BootstrapTestComparisonResult>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: BootstrapTestComparator<T> + ?Sized,
+{
+    let start = Instant::now();
+    let (behavior_1, behavior_2) = comparator.extract_representations(log_1, log_2)?;
+    let (log_1_size, log_2_size) = (behavior_1.len(), behavior_2.len());
+
+    let stoch_lang_1 = StochasticLanguage::from_items(behavior_1);
+    let stoch_lang_2 = StochasticLanguage::from_items(behavior_2);
+    let (log_1_variants, log_2_variants) =
+        (stoch_lang_1.variants.len(), stoch_lang_2.variants.len());
+
+    let logs_emd = compute_emd(
+        stoch_lang_1.frequencies.clone(),
+        stoch_lang_2.frequencies.clone(),
+        &comparator
+            .distance_matrix(
+                &stoch_lang_1.variants,
+                &stoch_lang_2.variants,
+                comparator.progress_reporter(),
+            )
+            .select(
+                &(0..stoch_lang_1.variants.len()).collect::<Vec<_>>(),
+                &(0..stoch_lang_2.variants.len()).collect::<Vec<_>>(),
+            ),
+    )
+    .emd;
+
+    let (bootstrap_emds, sequential) = match mode {
+        SamplingMode::Fixed(_) => (
+            comparator.bootstrap_emd_population(
+                stoch_lang_1,
+                resample_size,
+                distribution_size,
+                seed,
+            ),
+            None,
+        ),
+        SamplingMode::Sequential(options) => {
+            let progress = comparator.progress_reporter().start(
+                distribution_size as u64,
+                "Computing bootstrap EMD distribution (sequential)".into(),
+            );
+            let sampler = comparator
+                .bootstrap_sampler(stoch_lang_1, resample_size, seed)
+                .inspect(|_| progress.inc(1));
+            let (emds, summary) =
+                sequential_distribution(sampler, logs_emd, distribution_size, options);
+            progress.finish();
+            (emds, Some(summary))
+        }
+    };
+
+    let pvalue_estimate =
+        PValueEstimate::from_distribution(&bootstrap_emds, logs_emd, mode.pvalue_options());
+    let effect_size = EffectSize::from_distribution(&bootstrap_emds, logs_emd);
+
+    Ok(BootstrapTestComparisonResult {
+        logs_emd,
+        pvalue: pvalue_estimate.unbiased,
+        pvalue_estimate,
+        effect_size,
+        metadata: ComparisonMetadata {
+            comparator: comparator.name(),
+            parameters: comparator.parameters(),
+            seed,
+            log_1_size,
+            log_2_size,
+            log_1_variants,
+            log_2_variants,
+            distribution_size: bootstrap_emds.len(),
+            resample_size: Some(resample_size),
+            runtime_seconds: start.elapsed().as_secs_f64(),
+            crate_version: CRATE_VERSION.to_string(),
+        },
+        bootstrap_emds,
+        sequential,
+    })
+}
@@ -0,0 +1,73 @@
+use process_mining::EventLog;
+use serde::Serialize;
+
+use crate::{
+    binning::{
+        kmeans_binner::{KMeansArgs, KMeansBinner},
+        Binner,
+    },
+    comparators::common::{
+        extraction::EventAttributeProjection,
+        result::{config_parameters, ComparatorParameters},
+    },
+    distance::weighted_levenshtein::postnormalized_weighted_levenshtein_distance,
+    representation::ProjectedTrace,
+    utils::attributes::attribute_error::AttributeResult,
+};
+
+use super::bootstrap_comparator::BootstrapTestComparator;
+
+/// An implementation of the [`BootstrapTestComparator`] for comparisons of traces projected
+/// on arbitrary event attributes (see [`EventAttributeProjection`]), e.g.,
+/// (activity, channel) sequences. Numeric attributes are binned using a binner
+/// of type `B` (by default, K-Means++ clustering) or min-max scaled. Uses the
+/// postnormalized weighted Levenshtein distance as a distance notion between the
+/// projected traces.
+#[derive(Debug, Serialize)]
+#[serde(bound(serialize = "B::Args: Serialize"))]
+pub struct EventAttributeBootstrapComparator<B: Binner<f64> = KMeansBinner> {
+    projection: EventAttributeProjection,
+    binner_args: B::Args,
+}
+
+impl EventAttributeBootstrapComparator {
+    /// Compare the given projection, binning numeric attributes with K-Means++
+    /// clustering using default arguments.
+    pub fn new(projection: EventAttributeProjection) -> Self {
+        Self::with_binner_args(projection, KMeansArgs::default())
+    }
+}
+
+impl<B: Binner<f64>> EventAttributeBootstrapComparator<B> {
+    /// Compare the given projection, binning numeric attributes with a binner of
+    /// type `B`.
+    pub fn with_binner_args(projection: EventAttributeProjection, binner_args: B::Args) -> Self {
+        Self {
+            projection,
+            binner_args,
+        }
+    }
+}
+
+impl<B: Binner<f64>> BootstrapTestComparator<ProjectedTrace>
+    for EventAttributeBootstrapComparator<B>
+where
+    B::Args: Serialize,
+{
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<ProjectedTrace>, Vec<ProjectedTrace>)> {
+        self.projection
+            .project_logs::<B>(log_1, log_2, &self.binner_args)
+    }
+
+    fn cost(&self, rep_1: &ProjectedTrace, rep_2: &ProjectedTrace) -> f64 {
+        postnormalized_weighted_levenshtein_distance(rep_1, rep_2)
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        config_parameters(self)
+    }
+}
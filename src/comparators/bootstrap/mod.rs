@@ -1,3 +1,9 @@
 pub mod bootstrap_comparator;
+pub mod case_duration;
 pub mod control_flow;
+pub mod event_attributes;
+pub mod resource;
+pub mod service_waiting_time_levenshtein;
 pub mod timed_levenshtein;
+pub mod trace_attributes;
+pub mod waiting_time_levenshtein;
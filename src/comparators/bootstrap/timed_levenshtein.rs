@@ -1,12 +1,14 @@
 use process_mining::EventLog;
+use serde::Serialize;
 
 use crate::{
     binning::{
         kmeans_binner::{KMeansArgs, KMeansBinner},
         BinnerManager,
     },
-    comparators::common::extraction::{
-        apply_binner_manager_on_service_time_traces, extract_service_time_traces,
+    comparators::common::{
+        extraction::{apply_binner_manager_on_service_time_traces, extract_service_time_traces},
+        result::{config_parameters, ComparatorParameters},
     },
     distance::weighted_levenshtein::postnormalized_weighted_levenshtein_distance,
     utils::attributes::attribute_error::AttributeResult,
@@ -21,7 +23,7 @@ use super::bootstrap_comparator::BootstrapTestComparator;
 /// This is an extension to the Bootstrap Method proposed in "Statistical tests
 /// and association measures for business processes" by Leemans et al. and has not
 /// been evaluated anywhere.
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Serialize)]
 pub struct TimedLevenshteinBootstrapComparator {
     binner_args: KMeansArgs,
 }
@@ -62,4 +64,8 @@ impl BootstrapTestComparator<Vec<(String, usize)>> for TimedLevenshteinBootstrap
     fn cost(&self, rep_1: &Vec<(String, usize)>, rep_2: &Vec<(String, usize)>) -> f64 {
         postnormalized_weighted_levenshtein_distance(rep_1, rep_2)
     }
+
+    fn parameters(&self) -> ComparatorParameters {
+        config_parameters(self)
+    }
 }
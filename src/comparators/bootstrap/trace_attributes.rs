@@ -0,0 +1,83 @@
+use process_mining::EventLog;
+use serde::Serialize;
+
+use crate::{
+    binning::{
+        kmeans_binner::{KMeansArgs, KMeansBinner},
+        Binner,
+    },
+    comparators::common::{
+        extraction::{extract_trace_attribute_representations, TraceAttributeSpec},
+        result::{config_parameters, ComparatorParameters},
+    },
+    distance::gower::weighted_gower_distance,
+    representation::ProjectedAttributes,
+    utils::attributes::attribute_error::AttributeResult,
+};
+
+use super::bootstrap_comparator::BootstrapTestComparator;
+
+/// An implementation of the [`BootstrapTestComparator`] for comparing the joint
+/// distribution of trace-level attributes.
+///
+/// Each case is represented by a tuple of the configured trace attributes (see
+/// [`TraceAttributeSpec`]). Numeric attributes are binned using a binner of type
+/// `B` (by default, K-Means++ clustering), categorical attributes are kept as-is.
+/// The distance between cases is a weighted Gower-like distance, see
+/// [`weighted_gower_distance`].
+#[derive(Debug, Serialize)]
+#[serde(bound(serialize = "B::Args: Serialize"))]
+pub struct TraceAttributeBootstrapComparator<B: Binner<f64> = KMeansBinner> {
+    attributes: Vec<TraceAttributeSpec>,
+    #[serde(skip)]
+    weights: Vec<f64>,
+    binner_args: B::Args,
+}
+
+impl TraceAttributeBootstrapComparator {
+    /// Compare the given attributes, binning numeric attributes with K-Means++
+    /// clustering using default arguments.
+    pub fn new(attributes: Vec<TraceAttributeSpec>) -> Self {
+        Self::with_binner_args(attributes, KMeansArgs::default())
+    }
+}
+
+impl<B: Binner<f64>> TraceAttributeBootstrapComparator<B> {
+    /// Compare the given attributes, binning numeric attributes with a binner of
+    /// type `B`.
+    pub fn with_binner_args(attributes: Vec<TraceAttributeSpec>, binner_args: B::Args) -> Self {
+        let weights = attributes.iter().map(|spec| spec.weight).collect();
+        Self {
+            attributes,
+            weights,
+            binner_args,
+        }
+    }
+}
+
+impl<B: Binner<f64>> BootstrapTestComparator<ProjectedAttributes>
+    for TraceAttributeBootstrapComparator<B>
+where
+    B::Args: Serialize,
+{
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<ProjectedAttributes>, Vec<ProjectedAttributes>)> {
+        extract_trace_attribute_representations::<B>(
+            log_1,
+            log_2,
+            &self.attributes,
+            &self.binner_args,
+        )
+    }
+
+    fn cost(&self, rep_1: &ProjectedAttributes, rep_2: &ProjectedAttributes) -> f64 {
+        weighted_gower_distance(rep_1, rep_2, &self.weights)
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        config_parameters(self)
+    }
+}
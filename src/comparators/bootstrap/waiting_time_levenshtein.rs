@@ -0,0 +1,73 @@
+use process_mining::EventLog;
+use serde::Serialize;
+
+use crate::{
+    binning::{
+        kmeans_binner::{KMeansArgs, KMeansBinner},
+        BinnerManager,
+    },
+    comparators::common::{
+        extraction::{apply_binner_manager_on_service_time_traces, extract_waiting_time_traces},
+        result::{config_parameters, ComparatorParameters},
+    },
+    distance::weighted_levenshtein::postnormalized_weighted_levenshtein_distance,
+    utils::attributes::attribute_error::AttributeResult,
+};
+
+use super::bootstrap_comparator::BootstrapTestComparator;
+
+/// An implementation of the [`BootstrapTestComparator`] for timed control flow
+/// comparisons based on _waiting times_, i.e., the time between the completion
+/// of an event and the start of the next one. Uses K-Means++ clustering to bin waiting
+/// times and the postnormalized weighted Levenshtein distance as a distance notion
+/// between _waiting-time traces_.
+///
+/// This is an extension to the Bootstrap Method proposed in "Statistical tests
+/// and association measures for business processes" by Leemans et al. and has not
+/// been evaluated anywhere.
+#[derive(Default, Debug, Serialize)]
+pub struct WaitingTimeLevenshteinBootstrapComparator {
+    binner_args: KMeansArgs,
+}
+
+impl WaitingTimeLevenshteinBootstrapComparator {
+    pub fn new(binner_args: KMeansArgs) -> Self {
+        Self { binner_args }
+    }
+}
+
+impl BootstrapTestComparator<Vec<(String, usize)>> for WaitingTimeLevenshteinBootstrapComparator {
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<Vec<(String, usize)>>, Vec<Vec<(String, usize)>>)> {
+        let waiting_time_traces_1 = extract_waiting_time_traces(log_1)?;
+        let waiting_time_traces_2 = extract_waiting_time_traces(log_2)?;
+
+        let combined_data: Vec<_> = waiting_time_traces_1
+            .iter()
+            .chain(waiting_time_traces_2.iter())
+            .flatten()
+            .cloned()
+            .collect();
+
+        let binner_manager = BinnerManager::<f64, KMeansBinner>::from_key_value_pairs(
+            combined_data,
+            self.binner_args.clone(),
+        );
+
+        Ok((
+            apply_binner_manager_on_service_time_traces(waiting_time_traces_1, &binner_manager),
+            apply_binner_manager_on_service_time_traces(waiting_time_traces_2, &binner_manager),
+        ))
+    }
+
+    fn cost(&self, rep_1: &Vec<(String, usize)>, rep_2: &Vec<(String, usize)>) -> f64 {
+        postnormalized_weighted_levenshtein_distance(rep_1, rep_2)
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        config_parameters(self)
+    }
+}
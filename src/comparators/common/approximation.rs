@@ -0,0 +1,639 @@
+//! Approximate comparisons for exploratory analyses of very large event logs: Cap
+//! the number of distinct variants (or traces) before running the tests, trading
+//! exactness for speed.
+//!
+//! The approximation error is quantified by the approximated probability mass, i.e.,
+//! the total variation distance between the original and the reduced stochastic
+//! language of each event log.
+
+use std::{collections::HashMap, fmt::Debug, hash::Hash, sync::OnceLock};
+
+use itertools::Itertools;
+use ndarray::Array2;
+use process_mining::EventLog;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    comparators::{
+        bootstrap::bootstrap_comparator::{BootstrapTestComparator, BootstrapTestComparisonResult},
+        common::result::ComparatorParameters,
+        permutation_test::permutation_test_comparator::{
+            PermutationTestComparator, PermutationTestComparisonResult,
+        },
+    },
+    distance::matrix::DistanceMatrix,
+    utils::{attributes::attribute_error::AttributeResult, progress::ProgressReporter},
+};
+
+/// How the representations of the event logs are reduced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Approximation {
+    /// Keep the `max_variants` most frequent variants (over both event logs). The
+    /// traces of all other variants (the tail) are reassigned to the closest kept
+    /// variant according to the comparator's `cost`.
+    ///
+    /// At least one variant is kept, i.e., a `max_variants` of 0 is treated as 1.
+    TopVariants { max_variants: usize },
+    /// Subsample at most `max_traces` traces of each event log, stratified by variant:
+    /// Each variant keeps a share of traces proportional to its frequency (using
+    /// largest-remainder rounding), so rare variants may be dropped.
+    ///
+    /// Changes the number and order of the traces, so it cannot be combined with
+    /// tests relating the traces to each other (paired, stratified, k-sample tests or
+    /// drift detection); These return an `Err`.
+    Subsample { max_traces: usize },
+}
+
+/// How much the representations were changed by the [`Approximation`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApproximationSummary {
+    /// The number of distinct variants over both event logs before the approximation.
+    pub original_variants: usize,
+    /// The number of distinct variants over both event logs after the approximation.
+    pub approximated_variants: usize,
+    /// The approximated probability mass of the first event log: The total variation
+    /// distance between its original and reduced stochastic language, in `[0, 1]`.
+    pub approximated_mass_1: f64,
+    /// The approximated probability mass of the second event log.
+    pub approximated_mass_2: f64,
+}
+
+/// A comparator that reduces the extracted representations according to an
+/// [`Approximation`] before running the tests. Behaves like the wrapped comparator
+/// otherwise; The approximation is added to the parameters recorded in the
+/// [`ComparisonMetadata`].
+///
+/// Use [`compare_permutation`](ApproximateComparator::compare_permutation) or
+/// [`compare_bootstrap`](ApproximateComparator::compare_bootstrap) to also obtain
+/// the [`ApproximationSummary`] of a comparison.
+///
+/// [`ComparisonMetadata`]: super::result::ComparisonMetadata
+#[derive(Debug, Clone)]
+pub struct ApproximateComparator<C> {
+    comparator: C,
+    approximation: Approximation,
+}
+
+impl<C> ApproximateComparator<C> {
+    pub fn new(comparator: C, approximation: Approximation) -> Self {
+        Self {
+            comparator,
+            approximation,
+        }
+    }
+
+    pub fn into_inner(self) -> C {
+        self.comparator
+    }
+
+    /// Compare two event logs like [`PermutationTestComparator::compare`], returning
+    /// the summary of the approximation with the result.
+    pub fn compare_permutation<T>(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+        distribution_size: usize,
+        seed: Option<u64>,
+    ) -> AttributeResult<(PermutationTestComparisonResult, ApproximationSummary)>
+    where
+        T: Hash + Eq + Clone + Ord + Debug,
+        C: PermutationTestComparator<T>,
+    {
+        let summarizing = Summarizing::new(self);
+        let result = summarizing.compare(log_1, log_2, distribution_size, seed)?;
+        Ok((result, summarizing.into_summary()))
+    }
+
+    /// Compare two event logs like [`BootstrapTestComparator::compare`], returning
+    /// the summary of the approximation with the result.
+    pub fn compare_bootstrap<T>(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+        resample_size: usize,
+        distribution_size: usize,
+        seed: Option<u64>,
+    ) -> AttributeResult<(BootstrapTestComparisonResult, ApproximationSummary)>
+    where
+        T: Hash + Eq + Clone + Ord + Debug,
+        C: BootstrapTestComparator<T>,
+    {
+        let summarizing = Summarizing::new(self);
+        let result = summarizing.compare(log_1, log_2, resample_size, distribution_size, seed)?;
+        Ok((result, summarizing.into_summary()))
+    }
+
+    /// Reduce the representations of both event logs, and summarize the reduction.
+    fn approximate_representations<T: Hash + Eq + Clone + Ord>(
+        &self,
+        behavior_1: Vec<T>,
+        behavior_2: Vec<T>,
+        cost: impl Fn(&T, &T) -> f64,
+    ) -> (Vec<T>, Vec<T>, ApproximationSummary) {
+        let original_variants = behavior_1.iter().chain(behavior_2.iter()).unique().count();
+        let (approximated_1, approximated_2) = match self.approximation {
+            Approximation::TopVariants { max_variants } => {
+                top_variants(&behavior_1, &behavior_2, max_variants, cost)
+            }
+            Approximation::Subsample { max_traces } => (
+                stratified_subsample(&behavior_1, max_traces),
+                stratified_subsample(&behavior_2, max_traces),
+            ),
+        };
+
+        let summary = ApproximationSummary {
+            original_variants,
+            approximated_variants: approximated_1
+                .iter()
+                .chain(approximated_2.iter())
+                .unique()
+                .count(),
+            approximated_mass_1: total_variation_distance(&behavior_1, &approximated_1),
+            approximated_mass_2: total_variation_distance(&behavior_2, &approximated_2),
+        };
+        (approximated_1, approximated_2, summary)
+    }
+
+    fn approximation_parameters(
+        &self,
+        mut parameters: ComparatorParameters,
+    ) -> ComparatorParameters {
+        parameters.insert(
+            "approximation".to_string(),
+            serde_json::to_value(self.approximation).unwrap_or_default(),
+        );
+        parameters
+    }
+}
+
+/// An [`ApproximateComparator`] used for a single comparison, which keeps the
+/// [`ApproximationSummary`] of the representations it extracted.
+struct Summarizing<'a, C> {
+    comparator: &'a ApproximateComparator<C>,
+    summary: OnceLock<ApproximationSummary>,
+}
+
+impl<'a, C> Summarizing<'a, C> {
+    fn new(comparator: &'a ApproximateComparator<C>) -> Self {
+        Self {
+            comparator,
+            summary: OnceLock::new(),
+        }
+    }
+
+    fn record<T>(
+        &self,
+        (approximated_1, approximated_2, summary): (Vec<T>, Vec<T>, ApproximationSummary),
+    ) -> (Vec<T>, Vec<T>) {
+        // A comparison extracts the representations once
+        let _ = self.summary.set(summary);
+        (approximated_1, approximated_2)
+    }
+
+    fn into_summary(self) -> ApproximationSummary {
+        self.summary
+            .into_inner()
+            .expect("the comparison extracts the representations")
+    }
+}
+
+/// Keep the `max_variants` (at least one) most frequent variants, reassigning the
+/// traces of all other variants to the closest kept variant.
+fn top_variants<T: Hash + Eq + Clone + Ord>(
+    behavior_1: &[T],
+    behavior_2: &[T],
+    max_variants: usize,
+    cost: impl Fn(&T, &T) -> f64,
+) -> (Vec<T>, Vec<T>) {
+    let kept: Vec<&T> = behavior_1
+        .iter()
+        .chain(behavior_2.iter())
+        .counts()
+        .into_iter()
+        // Most frequent first, ties broken by the variant for determinism
+        .sorted_by(|(variant_1, count_1), (variant_2, count_2)| {
+            count_2.cmp(count_1).then_with(|| variant_1.cmp(variant_2))
+        })
+        .take(max_variants.max(1))
+        .map(|(variant, _)| variant)
+        .collect();
+
+    let mut replacements: HashMap<&T, &T> =
+        kept.iter().map(|variant| (*variant, *variant)).collect();
+    for variant in behavior_1.iter().chain(behavior_2.iter()) {
+        replacements.entry(variant).or_insert_with(|| {
+            kept.iter()
+                .min_by(|a, b| cost(variant, a).total_cmp(&cost(variant, b)))
+                .unwrap()
+        });
+    }
+    let replace = |behavior: &[T]| -> Vec<T> {
+        behavior
+            .iter()
+            .map(|variant| replacements[variant].clone())
+            .collect()
+    };
+    let approximated_1 = replace(behavior_1);
+    let approximated_2 = replace(behavior_2);
+    (approximated_1, approximated_2)
+}
+
+/// Subsample at most `max_traces` traces, keeping a number of traces of each variant
+/// proportional to its frequency.
+fn stratified_subsample<T: Hash + Eq + Clone + Ord>(behavior: &[T], max_traces: usize) -> Vec<T> {
+    if behavior.len() <= max_traces {
+        return behavior.to_vec();
+    }
+
+    let counts: Vec<(&T, usize)> = behavior.iter().counts().into_iter().sorted().collect();
+    let quota = |count: usize| count as f64 * max_traces as f64 / behavior.len() as f64;
+    let mut allocations: Vec<usize> = counts
+        .iter()
+        .map(|(_, count)| quota(*count).floor() as usize)
+        .collect();
+
+    // Distribute the remaining traces by the largest remainders
+    let remaining = max_traces - allocations.iter().sum::<usize>();
+    (0..counts.len())
+        .sorted_by(|a, b| {
+            let remainder = |i: usize| quota(counts[i].1) - allocations[i] as f64;
+            remainder(*b).total_cmp(&remainder(*a))
+        })
+        .take(remaining)
+        .collect_vec()
+        .into_iter()
+        .for_each(|i| allocations[i] += 1);
+
+    counts
+        .iter()
+        .zip(allocations)
+        .flat_map(|((variant, _), allocation)| (0..allocation).map(|_| (*variant).clone()))
+        .collect()
+}
+
+/// The total variation distance between the stochastic languages of two populations.
+fn total_variation_distance<T: Hash + Eq>(original: &[T], approximated: &[T]) -> f64 {
+    let original_counts = original.iter().counts();
+    let approximated_counts = approximated.iter().counts();
+    let frequency = |counts: &HashMap<&T, usize>, variant: &T, total: usize| {
+        counts.get(variant).copied().unwrap_or_default() as f64 / total.max(1) as f64
+    };
+
+    original_counts
+        .keys()
+        .chain(approximated_counts.keys())
+        .unique()
+        .map(|variant| {
+            (frequency(&original_counts, variant, original.len())
+                - frequency(&approximated_counts, variant, approximated.len()))
+            .abs()
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+impl<T, C> PermutationTestComparator<T> for ApproximateComparator<C>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: PermutationTestComparator<T>,
+{
+    fn cost(&self, rep_1: &T, rep_2: &T) -> f64 {
+        self.comparator.cost(rep_1, rep_2)
+    }
+
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<T>, Vec<T>)> {
+        let (behavior_1, behavior_2) = self.comparator.extract_representations(log_1, log_2)?;
+        let (approximated_1, approximated_2, _) =
+            self.approximate_representations(behavior_1, behavior_2, |rep_1, rep_2| {
+                self.comparator.cost(rep_1, rep_2)
+            });
+        Ok((approximated_1, approximated_2))
+    }
+
+    fn name(&self) -> String {
+        self.comparator.name()
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        self.approximation_parameters(self.comparator.parameters())
+    }
+
+    fn progress_reporter(&self) -> &dyn ProgressReporter {
+        self.comparator.progress_reporter()
+    }
+
+    fn variant_distance_matrix<'a>(
+        &'a self,
+        variants: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Box<dyn DistanceMatrix + 'a>
+    where
+        T: 'a,
+    {
+        self.comparator.variant_distance_matrix(variants, reporter)
+    }
+
+    fn compute_symmetric_distance_matrix(
+        &self,
+        variants: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Array2<f64> {
+        self.comparator
+            .compute_symmetric_distance_matrix(variants, reporter)
+    }
+}
+
+impl<T, C> BootstrapTestComparator<T> for ApproximateComparator<C>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: BootstrapTestComparator<T>,
+{
+    fn cost(&self, rep_1: &T, rep_2: &T) -> f64 {
+        self.comparator.cost(rep_1, rep_2)
+    }
+
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<T>, Vec<T>)> {
+        let (behavior_1, behavior_2) = self.comparator.extract_representations(log_1, log_2)?;
+        let (approximated_1, approximated_2, _) =
+            self.approximate_representations(behavior_1, behavior_2, |rep_1, rep_2| {
+                self.comparator.cost(rep_1, rep_2)
+            });
+        Ok((approximated_1, approximated_2))
+    }
+
+    fn name(&self) -> String {
+        self.comparator.name()
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        self.approximation_parameters(self.comparator.parameters())
+    }
+
+    fn progress_reporter(&self) -> &dyn ProgressReporter {
+        self.comparator.progress_reporter()
+    }
+
+    fn distance_matrix<'a>(
+        &'a self,
+        variants_1: &[T],
+        variants_2: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Box<dyn DistanceMatrix + 'a>
+    where
+        T: 'a,
+    {
+        self.comparator
+            .distance_matrix(variants_1, variants_2, reporter)
+    }
+
+    fn compute_distance_matrix(
+        &self,
+        variants_1: &[T],
+        variants_2: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Array2<f64> {
+        self.comparator
+            .compute_distance_matrix(variants_1, variants_2, reporter)
+    }
+}
+
+impl<T, C> PermutationTestComparator<T> for Summarizing<'_, C>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: PermutationTestComparator<T>,
+{
+    fn cost(&self, rep_1: &T, rep_2: &T) -> f64 {
+        PermutationTestComparator::cost(self.comparator, rep_1, rep_2)
+    }
+
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<T>, Vec<T>)> {
+        let inner = &self.comparator.comparator;
+        let (behavior_1, behavior_2) = inner.extract_representations(log_1, log_2)?;
+        Ok(self.record(self.comparator.approximate_representations(
+            behavior_1,
+            behavior_2,
+            |rep_1, rep_2| inner.cost(rep_1, rep_2),
+        )))
+    }
+
+    fn name(&self) -> String {
+        PermutationTestComparator::name(self.comparator)
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        PermutationTestComparator::parameters(self.comparator)
+    }
+
+    fn progress_reporter(&self) -> &dyn ProgressReporter {
+        PermutationTestComparator::progress_reporter(self.comparator)
+    }
+
+    fn variant_distance_matrix<'b>(
+        &'b self,
+        variants: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Box<dyn DistanceMatrix + 'b>
+    where
+        T: 'b,
+    {
+        self.comparator.variant_distance_matrix(variants, reporter)
+    }
+
+    fn compute_symmetric_distance_matrix(
+        &self,
+        variants: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Array2<f64> {
+        self.comparator
+            .compute_symmetric_distance_matrix(variants, reporter)
+    }
+}
+
+impl<T, C> BootstrapTestComparator<T> for Summarizing<'_, C>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: BootstrapTestComparator<T>,
+{
+    fn cost(&self, rep_1: &T, rep_2: &T) -> f64 {
+        BootstrapTestComparator::cost(self.comparator, rep_1, rep_2)
+    }
+
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<T>, Vec<T>)> {
+        let inner = &self.comparator.comparator;
+        let (behavior_1, behavior_2) = inner.extract_representations(log_1, log_2)?;
+        Ok(self.record(self.comparator.approximate_representations(
+            behavior_1,
+            behavior_2,
+            |rep_1, rep_2| inner.cost(rep_1, rep_2),
+        )))
+    }
+
+    fn name(&self) -> String {
+        BootstrapTestComparator::name(self.comparator)
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        BootstrapTestComparator::parameters(self.comparator)
+    }
+
+    fn progress_reporter(&self) -> &dyn ProgressReporter {
+        BootstrapTestComparator::progress_reporter(self.comparator)
+    }
+
+    fn distance_matrix<'b>(
+        &'b self,
+        variants_1: &[T],
+        variants_2: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Box<dyn DistanceMatrix + 'b>
+    where
+        T: 'b,
+    {
+        self.comparator
+            .distance_matrix(variants_1, variants_2, reporter)
+    }
+
+    fn compute_distance_matrix(
+        &self,
+        variants_1: &[T],
+        variants_2: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Array2<f64> {
+        self.comparator
+            .compute_distance_matrix(variants_1, variants_2, reporter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comparators::{
+            bootstrap::control_flow::ControlFlowBootstrapComparator,
+            common::comparison_error::ComparisonError,
+            permutation_test::{
+                control_flow::ControlFlowPermutationComparator, stratification::Stratification,
+            },
+        },
+        drift::{detect_drift, DriftOptions, Windowing},
+        stats::pvalue::PValueOptions,
+        utils::{attributes::add_or_overwrite_attribute, constants::TRACEID_KEY},
+    };
+    use process_mining::event_log::AttributeValue;
+    use process_mining_macros::event_log;
+
+    #[test]
+    fn test_top_variants() {
+        let log_1 = event_log!([a, b], [a, b], [a, b], [a, c, b]; base_timestamp=EPOCH);
+        let log_2 = event_log!([a, b], [x, y], [x, y], [x, y, z]; base_timestamp=EPOCH);
+        let comparator = ApproximateComparator::new(
+            ControlFlowPermutationComparator,
+            Approximation::TopVariants { max_variants: 2 },
+        );
+
+        let (result, summary) = comparator
+            .compare_permutation(&log_1, &log_2, 50, Some(0))
+            .unwrap();
+        assert_eq!(result.metadata.log_1_variants, 1);
+        assert_eq!(result.metadata.log_2_variants, 2);
+        assert_eq!(
+            result.metadata.parameters,
+            PermutationTestComparator::parameters(&comparator)
+        );
+        assert!(result.metadata.parameters.contains_key("approximation"));
+
+        // <a,c,b> is reassigned to <a,b> and <x,y,z> to <x,y>
+        assert_eq!(summary.original_variants, 4);
+        assert_eq!(summary.approximated_variants, 2);
+        assert_eq!(summary.approximated_mass_1, 0.25);
+        assert_eq!(summary.approximated_mass_2, 0.25);
+    }
+
+    #[test]
+    fn test_stratified_subsample() {
+        let behavior = [vec![0; 6], vec![1; 3], vec![2; 1]].concat();
+        let subsample = stratified_subsample(&behavior, 5);
+        assert_eq!(subsample, vec![0, 0, 0, 1, 1]);
+        assert!((total_variation_distance(&behavior, &subsample) - 0.1).abs() < 1e-9);
+
+        let log_1 = event_log!([a, b], [a, b], [a, c], [a, d]; base_timestamp=EPOCH);
+        let log_2 = event_log!([a, b], [a, c]; base_timestamp=EPOCH);
+        let comparator = ApproximateComparator::new(
+            ControlFlowBootstrapComparator,
+            Approximation::Subsample { max_traces: 2 },
+        );
+        let (result, summary) = comparator
+            .compare_bootstrap(&log_1, &log_2, 2, 20, Some(0))
+            .unwrap();
+        assert_eq!(result.metadata.log_1_size, 2);
+        assert_eq!(summary.approximated_mass_2, 0.0);
+    }
+
+    #[test]
+    fn test_subsample_of_related_traces() {
+        let mut log_1 = event_log!([a, b], [a, c]; base_timestamp=EPOCH);
+        let mut log_2 = event_log!([a, b], [a, d]; base_timestamp=EPOCH);
+        for log in [&mut log_1, &mut log_2] {
+            log.traces
+                .iter_mut()
+                .zip(["1", "2"])
+                .for_each(|(trace, id)| {
+                    add_or_overwrite_attribute(
+                        trace,
+                        TRACEID_KEY,
+                        AttributeValue::String(id.into()),
+                    )
+                });
+        }
+        let comparator = ApproximateComparator::new(
+            ControlFlowPermutationComparator,
+            Approximation::Subsample { max_traces: 1 },
+        );
+        let is_count_error = |error: ComparisonError| {
+            matches!(
+                error,
+                ComparisonError::RepresentationCount {
+                    expected: 2,
+                    found: 1
+                }
+            )
+        };
+
+        let stratified = comparator.compare_stratified(
+            &log_1,
+            &log_2,
+            10,
+            &Stratification::by(|_| "all".to_string()),
+            &PValueOptions::default(),
+            Some(0),
+        );
+        assert!(is_count_error(stratified.unwrap_err()));
+
+        let paired =
+            comparator.compare_paired(&log_1, &log_2, 10, &PValueOptions::default(), Some(0));
+        assert!(is_count_error(paired.unwrap_err()));
+
+        let drift = detect_drift(
+            &comparator,
+            &log_1,
+            &DriftOptions::new(Windowing::consecutive_traces(1)),
+        );
+        assert!(is_count_error(drift.unwrap_err()));
+    }
+}
@@ -0,0 +1,42 @@
+use thiserror::Error;
+
+use crate::utils::attributes::attribute_error::AttributeError;
+
+/// An error that can occur when comparing more than two event logs, or when the
+/// traces of the event logs are related to each other (e.g., paired or windowed).
+#[derive(Debug, Clone, Error)]
+pub enum ComparisonError {
+    #[error(transparent)]
+    Attribute(#[from] AttributeError),
+    /// Fewer event logs than the comparison requires were passed.
+    #[error("expected at least two event logs, found {found}")]
+    TooFewLogs { found: usize },
+    /// An event log without traces, whose stochastic language is empty.
+    #[error("event log {0} has no traces")]
+    EmptyLog(usize),
+    /// The comparator did not extract one representation per trace, e.g., because
+    /// it subsamples the traces. The traces can then not be related to their
+    /// representations.
+    #[error(
+        "expected one representation per trace ({expected}), but the comparator extracted {found}"
+    )]
+    RepresentationCount { expected: usize, found: usize },
+    /// No trace of the first event log has a partner in the second event log, so
+    /// there are no pairs to compare.
+    #[error("no trace of the first event log has a partner with the same trace id")]
+    NoMatchedTraces,
+}
+
+impl ComparisonError {
+    /// Check that `found` representations were extracted from `expected` traces.
+    pub(crate) fn check_representation_count(
+        expected: usize,
+        found: usize,
+    ) -> Result<(), ComparisonError> {
+        if expected == found {
+            Ok(())
+        } else {
+            Err(ComparisonError::RepresentationCount { expected, found })
+        }
+    }
+}
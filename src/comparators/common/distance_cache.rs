@@ -0,0 +1,553 @@
+//! Caching of the costs between variants across comparisons, e.g., when comparing
+//! overlapping event logs repeatedly with the same comparator.
+//!
+//! A [`DistanceCache`] stores the cost between pairs of variants per comparator
+//! configuration (its name and parameters, or an explicit cache key). Wrapping a
+//! comparator in a [`CachedComparator`] makes it look up costs in the cache when
+//! computing distance matrices, so only the costs for new pairs of variants are
+//! computed. The cache can be persisted to disk to be reused in later runs.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    fs::File,
+    hash::Hash,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use ndarray::Array2;
+use process_mining::EventLog;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    comparators::{
+        bootstrap::bootstrap_comparator::BootstrapTestComparator,
+        permutation_test::permutation_test_comparator::PermutationTestComparator,
+    },
+    utils::{attributes::attribute_error::AttributeResult, progress::ProgressReporter},
+};
+
+use super::result::{ComparatorParameters, ResultExportError};
+
+type PairCosts = HashMap<(String, String), f64>;
+
+/// A cache of the costs between pairs of variants, per comparator configuration.
+///
+/// Variants are identified by their `Debug` representation, so a single cache can
+/// be shared by comparators with different representation types. The costs are
+/// assumed to be symmetric, i.e., `cost(a, b) = cost(b, a)`.
+#[derive(Debug, Clone, Default)]
+pub struct DistanceCache {
+    costs: HashMap<String, PairCosts>,
+    hits: usize,
+    misses: usize,
+}
+
+impl DistanceCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty cache that can be shared between [`CachedComparator`]s.
+    pub fn shared() -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(Self::new()))
+    }
+
+    /// The cached cost between the variants with the keys `variant_1` and `variant_2`
+    /// for the comparator configuration `config`.
+    pub fn get(&self, config: &str, variant_1: &str, variant_2: &str) -> Option<f64> {
+        self.costs
+            .get(config)?
+            .get(&pair_key(variant_1, variant_2))
+            .copied()
+    }
+
+    pub fn insert(&mut self, config: &str, variant_1: &str, variant_2: &str, cost: f64) {
+        self.costs
+            .entry(config.to_string())
+            .or_default()
+            .insert(pair_key(variant_1, variant_2), cost);
+    }
+
+    /// Get the cached cost, or compute it using `cost` and insert it into the cache.
+    pub fn get_or_insert_with(
+        &mut self,
+        config: &str,
+        variant_1: &str,
+        variant_2: &str,
+        cost: impl FnOnce() -> f64,
+    ) -> f64 {
+        if let Some(cached) = self.get(config, variant_1, variant_2) {
+            self.hits += 1;
+            return cached;
+        }
+        self.misses += 1;
+        let cost = cost();
+        self.insert(config, variant_1, variant_2, cost);
+        cost
+    }
+
+    /// The number of cached costs over all comparator configurations.
+    pub fn len(&self) -> usize {
+        self.costs.values().map(HashMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of lookups answered from the cache since the cache was created
+    /// or loaded.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// The number of costs computed since the cache was created or loaded.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Remove all cached costs of a comparator configuration.
+    pub fn clear_config(&mut self, config: &str) {
+        self.costs.remove(config);
+    }
+
+    /// Write the cache as JSON to a file. JSON cannot represent non-finite costs, so
+    /// they are written as `null` and loaded as NaN.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ResultExportError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut writer, &SerializedDistanceCache::from(self))?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read a cache written with [`save`](DistanceCache::save).
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ResultExportError> {
+        let serialized: SerializedDistanceCache =
+            serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        Ok(serialized.into())
+    }
+
+    /// Read a cache written with [`save`](DistanceCache::save), or create an empty
+    /// cache if the file does not exist yet.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self, ResultExportError> {
+        if path.as_ref().exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::new())
+        }
+    }
+}
+
+/// The key of an unordered pair of variants.
+fn pair_key(variant_1: &str, variant_2: &str) -> (String, String) {
+    if variant_1 <= variant_2 {
+        (variant_1.to_string(), variant_2.to_string())
+    } else {
+        (variant_2.to_string(), variant_1.to_string())
+    }
+}
+
+/// The serialized form of a [`DistanceCache`]: The `(variant_1, variant_2, cost)`
+/// entries per comparator configuration.
+#[derive(Serialize, Deserialize)]
+struct SerializedDistanceCache {
+    costs: BTreeMap<String, Vec<(String, String, Option<f64>)>>,
+}
+
+impl From<&DistanceCache> for SerializedDistanceCache {
+    fn from(cache: &DistanceCache) -> Self {
+        let costs = cache
+            .costs
+            .iter()
+            .map(|(config, pair_costs)| {
+                let mut entries: Vec<(String, String, Option<f64>)> = pair_costs
+                    .iter()
+                    .map(|((variant_1, variant_2), cost)| {
+                        (variant_1.clone(), variant_2.clone(), Some(*cost))
+                    })
+                    .collect();
+                entries.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+                (config.clone(), entries)
+            })
+            .collect();
+        Self { costs }
+    }
+}
+
+impl From<SerializedDistanceCache> for DistanceCache {
+    fn from(serialized: SerializedDistanceCache) -> Self {
+        let costs = serialized
+            .costs
+            .into_iter()
+            .map(|(config, entries)| {
+                let pair_costs = entries
+                    .into_iter()
+                    .map(|(variant_1, variant_2, cost)| {
+                        (pair_key(&variant_1, &variant_2), cost.unwrap_or(f64::NAN))
+                    })
+                    .collect();
+                (config, pair_costs)
+            })
+            .collect();
+        Self {
+            costs,
+            ..Default::default()
+        }
+    }
+}
+
+/// A comparator that looks up the costs between variants in a (shared) [`DistanceCache`]
+/// when computing distance matrices. Behaves exactly like the wrapped comparator
+/// otherwise, including its name and parameters in the [`ComparisonMetadata`].
+///
+/// The costs are cached per comparator configuration, identified by the name and
+/// parameters of the comparator (see [`config_key`]). A comparator without
+/// parameters cannot be told apart from differently configured instances of the
+/// same type, so its costs are only cached with an explicit key (see
+/// [`with_cache_key`](CachedComparator::with_cache_key)), and computed without the
+/// cache otherwise.
+///
+/// [`ComparisonMetadata`]: super::result::ComparisonMetadata
+#[derive(Debug, Clone)]
+pub struct CachedComparator<C> {
+    comparator: C,
+    cache: Arc<Mutex<DistanceCache>>,
+    cache_key: Option<String>,
+}
+
+impl<C> CachedComparator<C> {
+    pub fn new(comparator: C, cache: Arc<Mutex<DistanceCache>>) -> Self {
+        Self {
+            comparator,
+            cache,
+            cache_key: None,
+        }
+    }
+
+    /// Cache the costs under `cache_key` instead of the name and parameters of the
+    /// comparator. The key must identify the configuration of the comparator, i.e.,
+    /// differently configured comparators must use different keys.
+    pub fn with_cache_key(mut self, cache_key: impl Into<String>) -> Self {
+        self.cache_key = Some(cache_key.into());
+        self
+    }
+
+    pub fn cache(&self) -> &Arc<Mutex<DistanceCache>> {
+        &self.cache
+    }
+
+    pub fn into_inner(self) -> C {
+        self.comparator
+    }
+
+    /// The key of the configuration of the comparator in the cache, or `None` if
+    /// the costs should not be cached.
+    fn config(&self, name: String, parameters: ComparatorParameters) -> Option<String> {
+        match &self.cache_key {
+            Some(cache_key) => Some(cache_key.clone()),
+            None if parameters.is_empty() => None,
+            None => Some(config_key(name, parameters)),
+        }
+    }
+
+    /// Compute the matrix of costs between `variants_1` and `variants_2`, looking
+    /// up the costs of known pairs in the cache.
+    ///
+    /// The cache is only locked to look up the known costs and to insert the new
+    /// ones, so comparators sharing the cache can compute costs concurrently.
+    fn cached_distance_matrix<T: Debug>(
+        &self,
+        config: &str,
+        variants_1: &[T],
+        variants_2: &[T],
+        cost: impl Fn(&T, &T) -> f64,
+        progress_reporter: &dyn ProgressReporter,
+    ) -> Array2<f64> {
+        let keys_1: Vec<String> = variants_1.iter().map(|v| format!("{v:?}")).collect();
+        let keys_2: Vec<String> = variants_2.iter().map(|v| format!("{v:?}")).collect();
+        let progress = progress_reporter.start(
+            variants_1.len() as u64 * variants_2.len() as u64,
+            format!(
+                "Computing distance matrix using cache ({}x{})",
+                variants_1.len(),
+                variants_2.len(),
+            ),
+        );
+
+        let mut dists = Array2::zeros((variants_1.len(), variants_2.len()));
+        let mut missing: Vec<(usize, usize)> = Vec::new();
+        {
+            let mut cache = self.cache.lock().unwrap();
+            for ((i, j), dist) in dists.indexed_iter_mut() {
+                match cache.get(config, &keys_1[i], &keys_2[j]) {
+                    Some(cached) => {
+                        cache.hits += 1;
+                        *dist = cached;
+                        progress.inc(1);
+                    }
+                    None => missing.push((i, j)),
+                }
+            }
+        }
+
+        // Compute each missing pair once, e.g., only one of (a, b) and (b, a)
+        let mut computed: PairCosts = HashMap::new();
+        let mut hits = 0;
+        for (i, j) in missing {
+            let key = pair_key(&keys_1[i], &keys_2[j]);
+            dists[(i, j)] = match computed.get(&key) {
+                Some(cost) => {
+                    hits += 1;
+                    *cost
+                }
+                None => {
+                    let value = cost(&variants_1[i], &variants_2[j]);
+                    computed.insert(key, value);
+                    value
+                }
+            };
+            progress.inc(1);
+        }
+
+        let mut cache = self.cache.lock().unwrap();
+        cache.hits += hits;
+        cache.misses += computed.len();
+        cache
+            .costs
+            .entry(config.to_string())
+            .or_default()
+            .extend(computed);
+        drop(cache);
+        progress.finish();
+        dists
+    }
+}
+
+/// The key of a comparator configuration in the [`DistanceCache`], from the `name`
+/// and `parameters` of the comparator.
+pub fn config_key(name: String, parameters: ComparatorParameters) -> String {
+    format!(
+        "{name}{}",
+        serde_json::Value::Object(parameters.into_iter().collect())
+    )
+}
+
+impl<T, C> PermutationTestComparator<T> for CachedComparator<C>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: PermutationTestComparator<T>,
+{
+    fn cost(&self, rep_1: &T, rep_2: &T) -> f64 {
+        self.comparator.cost(rep_1, rep_2)
+    }
+
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<T>, Vec<T>)> {
+        self.comparator.extract_representations(log_1, log_2)
+    }
+
+    fn name(&self) -> String {
+        self.comparator.name()
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        self.comparator.parameters()
+    }
+
+    fn progress_reporter(&self) -> &dyn ProgressReporter {
+        self.comparator.progress_reporter()
+    }
+
+    fn compute_symmetric_distance_matrix(
+        &self,
+        variants: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Array2<f64> {
+        let Some(config) = self.config(self.name(), self.parameters()) else {
+            return self
+                .comparator
+                .compute_symmetric_distance_matrix(variants, reporter);
+        };
+        self.cached_distance_matrix(
+            &config,
+            variants,
+            variants,
+            |rep_1, rep_2| self.comparator.cost(rep_1, rep_2),
+            reporter,
+        )
+    }
+}
+
+impl<T, C> BootstrapTestComparator<T> for CachedComparator<C>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: BootstrapTestComparator<T>,
+{
+    fn cost(&self, rep_1: &T, rep_2: &T) -> f64 {
+        self.comparator.cost(rep_1, rep_2)
+    }
+
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<T>, Vec<T>)> {
+        self.comparator.extract_representations(log_1, log_2)
+    }
+
+    fn name(&self) -> String {
+        self.comparator.name()
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        self.comparator.parameters()
+    }
+
+    fn progress_reporter(&self) -> &dyn ProgressReporter {
+        self.comparator.progress_reporter()
+    }
+
+    fn compute_distance_matrix(
+        &self,
+        variants_1: &[T],
+        variants_2: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Array2<f64> {
+        let Some(config) = self.config(self.name(), self.parameters()) else {
+            return self
+                .comparator
+                .compute_distance_matrix(variants_1, variants_2, reporter);
+        };
+        self.cached_distance_matrix(
+            &config,
+            variants_1,
+            variants_2,
+            |rep_1, rep_2| self.comparator.cost(rep_1, rep_2),
+            reporter,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comparators::permutation_test::control_flow::ControlFlowPermutationComparator;
+    use process_mining_macros::event_log;
+
+    #[test]
+    fn test_cached_comparator_reuses_costs() {
+        let cache = DistanceCache::shared();
+        let comparator = CachedComparator::new(ControlFlowPermutationComparator, cache.clone())
+            .with_cache_key("control_flow");
+
+        let log_1 = event_log!([a, b], [a, c]; base_timestamp=EPOCH);
+        let log_2 = event_log!([a, b], [a, d]; base_timestamp=EPOCH);
+        let uncached = ControlFlowPermutationComparator
+            .compare(&log_1, &log_2, 50, Some(3))
+            .unwrap();
+        let result = comparator.compare(&log_1, &log_2, 50, Some(3)).unwrap();
+        assert_eq!(result.logs_emd, uncached.logs_emd);
+        assert_eq!(result.permutation_emds, uncached.permutation_emds);
+        assert_eq!(
+            result.metadata.comparator,
+            "ControlFlowPermutationComparator"
+        );
+
+        // 3 variants: 6 unordered pairs (including the diagonal)
+        assert_eq!(cache.lock().unwrap().len(), 6);
+        let misses = cache.lock().unwrap().misses();
+
+        // Only the pairs with the new variant <a,e> are computed
+        let log_3 = event_log!([a, b], [a, e]; base_timestamp=EPOCH);
+        comparator.compare(&log_1, &log_3, 50, Some(3)).unwrap();
+        let cache = cache.lock().unwrap();
+        assert_eq!(cache.len(), 9);
+        assert_eq!(cache.misses() - misses, 3);
+
+        assert_eq!(
+            cache.get("control_flow", "[\"a\", \"c\"]", "[\"a\", \"b\"]"),
+            Some(0.5)
+        );
+    }
+
+    /// Checks that the cache is not locked while computing costs.
+    struct LockCheckingComparator {
+        cache: Arc<Mutex<DistanceCache>>,
+    }
+
+    impl PermutationTestComparator<Vec<String>> for LockCheckingComparator {
+        fn cost(&self, rep_1: &Vec<String>, rep_2: &Vec<String>) -> f64 {
+            assert!(self.cache.try_lock().is_ok());
+            ControlFlowPermutationComparator.cost(rep_1, rep_2)
+        }
+
+        fn extract_representations(
+            &self,
+            log_1: &EventLog,
+            log_2: &EventLog,
+        ) -> AttributeResult<(Vec<Vec<String>>, Vec<Vec<String>>)> {
+            ControlFlowPermutationComparator.extract_representations(log_1, log_2)
+        }
+    }
+
+    #[test]
+    fn test_costs_are_computed_without_lock() {
+        let cache = DistanceCache::shared();
+        let comparator = CachedComparator::new(
+            LockCheckingComparator {
+                cache: cache.clone(),
+            },
+            cache.clone(),
+        )
+        .with_cache_key("lock_checking");
+
+        let log_1 = event_log!([a, b], [a, c]; base_timestamp=EPOCH);
+        let log_2 = event_log!([a, b], [a, d]; base_timestamp=EPOCH);
+        comparator.compare(&log_1, &log_2, 10, Some(3)).unwrap();
+        let cache = cache.lock().unwrap();
+        assert_eq!(cache.len(), 6);
+        // The 9 entries of the matrix contain 6 unordered pairs
+        assert_eq!((cache.hits(), cache.misses()), (3, 6));
+    }
+
+    #[test]
+    fn test_comparator_without_parameters_is_not_cached() {
+        let cache = DistanceCache::shared();
+        let comparator = CachedComparator::new(ControlFlowPermutationComparator, cache.clone());
+
+        let log_1 = event_log!([a, b], [a, c]; base_timestamp=EPOCH);
+        let log_2 = event_log!([a, b], [a, d]; base_timestamp=EPOCH);
+        comparator.compare(&log_1, &log_2, 10, Some(3)).unwrap();
+        assert!(cache.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut cache = DistanceCache::new();
+        cache.insert("config", "a", "b", 0.5);
+        cache.insert("config", "a", "c", f64::NAN);
+
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!(
+            "pcomprs_test_distance_cache_{}_{nanos}.json",
+            std::process::id()
+        ));
+        cache.save(&path).unwrap();
+        let loaded = DistanceCache::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.get("config", "b", "a"), Some(0.5));
+        assert!(loaded.get("config", "a", "c").unwrap().is_nan());
+    }
+}
@@ -1,8 +1,30 @@
-use process_mining::{event_log::Trace, EventLog};
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset, TimeDelta};
+use process_mining::{
+    event_log::{AttributeValue, Event, Trace},
+    EventLog,
+};
+use serde::Serialize;
 
 use crate::{
     binning::{Binner, BinnerManager},
-    utils::attributes::{attribute_error::AttributeResult, get_activity_label, get_service_time},
+    representation::{
+        ProjectedAttributes, ProjectedTrace, ProjectedValue, ServiceWaitingTimeEvent,
+        ServiceWaitingTimeTrace,
+    },
+    utils::{
+        attributes::{
+            attribute_error::{
+                AttributeError, AttributeErrorKind, AttributeLevel, AttributeResult,
+            },
+            coercion::{coerce_to_string, NumericCoercion},
+            get_activity_label, get_complete_timestamp, get_service_time, get_start_timestamp,
+            HasAttributes,
+        },
+        constants::{GROUP_KEY, RESOURCE_KEY, ROLE_KEY},
+        ordered_float::OrderedFloat,
+    },
 };
 
 /// Extract a sequence of activities from a [`Trace`].
@@ -25,6 +47,113 @@ pub fn project_traces_on_activity(log: &EventLog) -> AttributeResult<Vec<Vec<Str
     log.traces.iter().map(project_trace_on_activity).collect()
 }
 
+/// The organizational attribute describing who executed an event.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum ResourcePerspective {
+    /// The resource executing the event (`org:resource`).
+    #[default]
+    Resource,
+    /// The role of the resource executing the event (`org:role`).
+    Role,
+    /// The group of the resource executing the event (`org:group`).
+    Group,
+}
+
+impl ResourcePerspective {
+    /// The attribute key of the perspective.
+    pub fn key(&self) -> &'static str {
+        match self {
+            ResourcePerspective::Resource => RESOURCE_KEY,
+            ResourcePerspective::Role => ROLE_KEY,
+            ResourcePerspective::Group => GROUP_KEY,
+        }
+    }
+
+    /// Get the value of the perspective's attribute of an event.
+    ///
+    /// Returns an [`AttributeError`] if the attribute is missing or not a String.
+    ///
+    /// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+    pub fn get(&self, event: &Event) -> AttributeResult<String> {
+        event.get_string_by_key(self.key())
+    }
+}
+
+/// Extract the sequence of resources (or roles, or groups) executing the events
+/// of a [`Trace`].
+///
+/// Returns an [`AttributeError`] if the attribute of the chosen [`ResourcePerspective`]
+/// is missing on any event or not a String.
+///
+/// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+pub fn project_trace_on_resource(
+    trace: &Trace,
+    perspective: ResourcePerspective,
+) -> AttributeResult<Vec<String>> {
+    trace
+        .events
+        .iter()
+        .map(|evt| perspective.get(evt))
+        .collect()
+}
+
+/// Extract the sequence of resources (or roles, or groups) for each [`Trace`] in
+/// the event log. See [`project_trace_on_resource`].
+///
+/// Returns an [`AttributeError`] if the attribute of the chosen [`ResourcePerspective`]
+/// is missing on any event or not a String.
+///
+/// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+pub fn project_traces_on_resource(
+    log: &EventLog,
+    perspective: ResourcePerspective,
+) -> AttributeResult<Vec<Vec<String>>> {
+    log.traces
+        .iter()
+        .map(|trace| project_trace_on_resource(trace, perspective))
+        .collect()
+}
+
+/// Extract a sequence of (activity, resource) pairs from a [`Trace`], where the
+/// resource is given by the chosen [`ResourcePerspective`].
+///
+/// Returns an [`AttributeError`] if:
+///
+/// - The trace is missing the `concept:name` attribute or it is not a String.
+/// - The trace is missing the attribute of the [`ResourcePerspective`] or it is
+///   not a String.
+///
+/// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+pub fn project_trace_on_activity_resource(
+    trace: &Trace,
+    perspective: ResourcePerspective,
+) -> AttributeResult<Vec<(String, String)>> {
+    trace
+        .events
+        .iter()
+        .map(|evt| Ok((get_activity_label(evt)?, perspective.get(evt)?)))
+        .collect()
+}
+
+/// Extract a sequence of (activity, resource) pairs for each [`Trace`] in the
+/// event log. See [`project_trace_on_activity_resource`].
+///
+/// Returns an [`AttributeError`] if for any trace:
+///
+/// - The `concept:name` attribute is missing or it is not a String.
+/// - The attribute of the [`ResourcePerspective`] is missing or it is not a String.
+///
+/// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+pub fn project_traces_on_activity_resource(
+    log: &EventLog,
+    perspective: ResourcePerspective,
+) -> AttributeResult<Vec<Vec<(String, String)>>> {
+    log.traces
+        .iter()
+        .map(|trace| project_trace_on_activity_resource(trace, perspective))
+        .collect()
+}
+
 /// Extract a _service time trace_ from a [`Trace`], i.e., a sequence of tuples of
 /// activity and service time.
 ///
@@ -64,7 +193,159 @@ pub fn extract_service_time_traces(log: &EventLog) -> AttributeResult<Vec<Vec<(S
     log.traces.iter().map(trace_to_service_time_trace).collect()
 }
 
+/// Extract a _waiting time trace_ from a [`Trace`], i.e., a sequence of tuples of
+/// activity and waiting time (in seconds).
+///
+/// The waiting time of an event is the time between the completion (`time:timestamp`)
+/// of the previous event in the trace and its own start (`start_timestamp`). The
+/// first event of a trace has a waiting time of 0. If an event starts before the
+/// previous event completes (e.g., due to concurrency), its waiting time is 0.
+///
+/// Returns an [`AttributeError`] if:
+///
+/// - The trace is missing the `concept:name` attribute or it is not a String.
+/// - The trace is missing the `time:timestamp` attribute or it is not a [`DateTime`].
+/// - The trace is missing the `start_timestamp` attribute or it is not a [`DateTime`].
+///
+/// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+/// [`DateTime`]: chrono::DateTime
+pub fn trace_to_waiting_time_trace(trace: &Trace) -> AttributeResult<Vec<(String, f64)>> {
+    let mut previous_completion: Option<DateTime<FixedOffset>> = None;
+    trace
+        .events
+        .iter()
+        .map(|evt| {
+            let start = get_start_timestamp(evt)?;
+            let waiting_time = previous_completion
+                .map(|completion| (start - completion).max(TimeDelta::zero()))
+                .unwrap_or_default();
+            previous_completion = Some(get_complete_timestamp(evt)?);
+
+            Ok((
+                get_activity_label(evt)?,
+                waiting_time.num_milliseconds() as f64 / 1000.0,
+            ))
+        })
+        .collect()
+}
+
+/// Extract a _waiting time trace_ for each [`Trace`] in the event log. I.e., a
+/// sequence of tuples of activity and waiting time (in seconds). See
+/// [`trace_to_waiting_time_trace`].
+///
+/// Returns an [`AttributeError`] if for any trace:
+///
+/// - The `concept:name` attribute is missing or it is not a String.
+/// - The `time:timestamp` attribute is missing or it is not a [`DateTime`].
+/// - The `start_timestamp` attribute is missing or it is not a [`DateTime`].
+///
+/// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+/// [`DateTime`]: chrono::DateTime
+pub fn extract_waiting_time_traces(log: &EventLog) -> AttributeResult<Vec<Vec<(String, f64)>>> {
+    log.traces.iter().map(trace_to_waiting_time_trace).collect()
+}
+
+/// Extract a trace of (activity, service time, waiting time) tuples from a [`Trace`].
+/// Times are given in seconds. See [`trace_to_service_time_trace`] and
+/// [`trace_to_waiting_time_trace`].
+///
+/// Returns an [`AttributeError`] if:
+///
+/// - The trace is missing the `concept:name` attribute or it is not a String.
+/// - The trace is missing the `time:timestamp` attribute or it is not a [`DateTime`].
+/// - The trace is missing the `start_timestamp` attribute or it is not a [`DateTime`].
+///
+/// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+pub fn trace_to_service_waiting_time_trace(
+    trace: &Trace,
+) -> AttributeResult<Vec<(String, f64, f64)>> {
+    Ok(trace_to_service_time_trace(trace)?
+        .into_iter()
+        .zip(trace_to_waiting_time_trace(trace)?)
+        .map(|((activity, service_time), (_, waiting_time))| (activity, service_time, waiting_time))
+        .collect())
+}
+
+/// Extract a trace of (activity, service time, waiting time) tuples for each [`Trace`]
+/// in the event log. See [`trace_to_service_waiting_time_trace`].
+///
+/// Returns an [`AttributeError`] if for any trace:
+///
+/// - The `concept:name` attribute is missing or it is not a String.
+/// - The `time:timestamp` attribute is missing or it is not a [`DateTime`].
+/// - The `start_timestamp` attribute is missing or it is not a [`DateTime`].
+///
+/// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+pub fn extract_service_waiting_time_traces(
+    log: &EventLog,
+) -> AttributeResult<Vec<Vec<(String, f64, f64)>>> {
+    log.traces
+        .iter()
+        .map(trace_to_service_waiting_time_trace)
+        .collect()
+}
+
+/// Extract traces of [`ServiceWaitingTimeEvent`]s from two event logs.
+///
+/// Service times and waiting times are binned separately, using a binner of type
+/// `B` per activity, trained on the data of both event logs.
+///
+/// Returns an [`AttributeError`] if for any trace:
+///
+/// - The `concept:name` attribute is missing or it is not a String.
+/// - The `time:timestamp` attribute is missing or it is not a [`DateTime`].
+/// - The `start_timestamp` attribute is missing or it is not a [`DateTime`].
+///
+/// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+pub fn extract_service_waiting_time_representations<B: Binner<f64>>(
+    log_1: &EventLog,
+    log_2: &EventLog,
+    binner_args: &B::Args,
+) -> AttributeResult<(Vec<ServiceWaitingTimeTrace>, Vec<ServiceWaitingTimeTrace>)> {
+    let traces_1 = extract_service_waiting_time_traces(log_1)?;
+    let traces_2 = extract_service_waiting_time_traces(log_2)?;
+
+    let (service_time_data, waiting_time_data): (Vec<_>, Vec<_>) = traces_1
+        .iter()
+        .chain(traces_2.iter())
+        .flatten()
+        .map(|(activity, service_time, waiting_time)| {
+            (
+                (activity.clone(), *service_time),
+                (activity.clone(), *waiting_time),
+            )
+        })
+        .unzip();
+    let service_time_binner =
+        BinnerManager::<f64, B>::from_key_value_pairs(service_time_data, binner_args.clone());
+    let waiting_time_binner =
+        BinnerManager::<f64, B>::from_key_value_pairs(waiting_time_data, binner_args.clone());
+
+    let to_events = |traces: Vec<Vec<(String, f64, f64)>>| -> Vec<ServiceWaitingTimeTrace> {
+        traces
+            .into_iter()
+            .map(|trace| {
+                trace
+                    .into_iter()
+                    .map(
+                        |(activity, service_time, waiting_time)| ServiceWaitingTimeEvent {
+                            service_time: service_time_binner.bin(&activity, service_time),
+                            waiting_time: waiting_time_binner.bin(&activity, waiting_time),
+                            activity,
+                        },
+                    )
+                    .collect()
+            })
+            .collect()
+    };
+
+    Ok((to_events(traces_1), to_events(traces_2)))
+}
+
 /// Apply binning to a service time trace.
+///
+/// Also applicable to other traces of (activity, duration) pairs, such as waiting
+/// time traces.
 pub fn apply_binner_manager_on_service_time_trace<T: Binner<f64>>(
     service_time_trace: Vec<(String, f64)>,
     binner_manager: &BinnerManager<f64, T>,
@@ -79,6 +360,9 @@ pub fn apply_binner_manager_on_service_time_trace<T: Binner<f64>>(
 }
 
 /// Apply binning to service time traces.
+///
+/// Also applicable to other traces of (activity, duration) pairs, such as waiting
+/// time traces.
 pub fn apply_binner_manager_on_service_time_traces<T: Binner<f64>>(
     service_time_traces: Vec<Vec<(String, f64)>>,
     binner_manager: &BinnerManager<f64, T>,
@@ -90,3 +374,603 @@ pub fn apply_binner_manager_on_service_time_traces<T: Binner<f64>>(
         })
         .collect()
 }
+
+/// Compute the _case duration_ (throughput time) of a [`Trace`] in seconds, i.e.,
+/// the time between the first and the last `time:timestamp` of the trace.
+///
+/// If `consider_start_timestamp` is `true`, the `start_timestamp` of the events is
+/// also taken into account, so the case duration starts with the earliest start of
+/// an event. An empty trace has a duration of 0.
+///
+/// Returns an [`AttributeError`] if:
+///
+/// - The `time:timestamp` attribute of any event is missing or not a [`DateTime`].
+/// - `consider_start_timestamp` is `true` and the `start_timestamp` attribute of
+///   any event is missing or not a [`DateTime`].
+///
+/// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+/// [`DateTime`]: chrono::DateTime
+pub fn trace_to_case_duration(
+    trace: &Trace,
+    consider_start_timestamp: bool,
+) -> AttributeResult<f64> {
+    let mut first = None;
+    let mut last = None;
+
+    for evt in trace.events.iter() {
+        let complete = get_complete_timestamp(evt)?;
+        let start = if consider_start_timestamp {
+            get_start_timestamp(evt)?.min(complete)
+        } else {
+            complete
+        };
+
+        first = Some(first.map_or(start, |t| start.min(t)));
+        last = Some(last.map_or(complete, |t| complete.max(t)));
+    }
+
+    Ok(match (first, last) {
+        (Some(first), Some(last)) => (last - first).num_milliseconds() as f64 / 1000.0,
+        _ => 0.0,
+    })
+}
+
+/// Compute the _case duration_ (throughput time) in seconds for each [`Trace`] in
+/// the event log. See [`trace_to_case_duration`].
+///
+/// Returns an [`AttributeError`] if for any trace:
+///
+/// - The `time:timestamp` attribute is missing or not a [`DateTime`].
+/// - `consider_start_timestamp` is `true` and the `start_timestamp` attribute is
+///   missing or not a [`DateTime`].
+///
+/// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+/// [`DateTime`]: chrono::DateTime
+pub fn extract_case_durations(
+    log: &EventLog,
+    consider_start_timestamp: bool,
+) -> AttributeResult<Vec<f64>> {
+    log.traces
+        .iter()
+        .map(|trace| trace_to_case_duration(trace, consider_start_timestamp))
+        .collect()
+}
+
+/// Extract the case durations of two event logs for a comparison.
+///
+/// If `binner_args` is `Some`, a single binner is trained on the case durations of
+/// _both_ event logs, and the case durations are replaced by their bin index.
+/// Otherwise, the case durations (in seconds) are used as-is.
+pub fn extract_case_duration_representations<B: Binner<f64>>(
+    log_1: &EventLog,
+    log_2: &EventLog,
+    consider_start_timestamp: bool,
+    binner_args: Option<&B::Args>,
+) -> AttributeResult<(Vec<OrderedFloat>, Vec<OrderedFloat>)> {
+    let durations_1 = extract_case_durations(log_1, consider_start_timestamp)?;
+    let durations_2 = extract_case_durations(log_2, consider_start_timestamp)?;
+
+    Ok(match binner_args {
+        Some(args) => {
+            let combined_data: Vec<f64> = durations_1
+                .iter()
+                .chain(durations_2.iter())
+                .copied()
+                .collect();
+            let binner = B::new(combined_data, args.clone());
+
+            let apply_binner = |durations: Vec<f64>| {
+                durations
+                    .into_iter()
+                    .map(|duration| OrderedFloat(binner.bin(duration) as f64))
+                    .collect()
+            };
+            (apply_binner(durations_1), apply_binner(durations_2))
+        }
+        None => (
+            durations_1.into_iter().map(OrderedFloat).collect(),
+            durations_2.into_iter().map(OrderedFloat).collect(),
+        ),
+    })
+}
+
+/// How a trace attribute is treated in a comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TraceAttributeKind {
+    /// The attribute is numeric (see [`TraceAttributeSpec::numeric_coercion`]), and
+    /// is binned before the comparison.
+    Numeric,
+    /// The attribute is a string, which is compared as-is.
+    Categorical,
+}
+
+/// The specification of a trace attribute to consider in a comparison.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceAttributeSpec {
+    /// The key of the trace attribute.
+    pub key: String,
+    /// How the attribute is treated.
+    pub kind: TraceAttributeKind,
+    /// The weight of the attribute in the distance between cases.
+    pub weight: f64,
+    /// The coercions applied when reading numeric attributes.
+    pub numeric_coercion: NumericCoercion,
+}
+
+impl TraceAttributeSpec {
+    /// A numeric attribute with weight 1.
+    pub fn numeric(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            kind: TraceAttributeKind::Numeric,
+            weight: 1.0,
+            numeric_coercion: NumericCoercion::default(),
+        }
+    }
+
+    /// A categorical attribute with weight 1.
+    pub fn categorical(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            kind: TraceAttributeKind::Categorical,
+            weight: 1.0,
+            numeric_coercion: NumericCoercion::default(),
+        }
+    }
+
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn with_numeric_coercion(mut self, numeric_coercion: NumericCoercion) -> Self {
+        self.numeric_coercion = numeric_coercion;
+        self
+    }
+}
+
+/// Extract the configured trace attributes from two event logs.
+///
+/// Each case is represented as a tuple of its attribute values in the order of
+/// `attributes`. Numeric attributes are binned using a [`BinnerManager`] with one
+/// binner per attribute, trained on both event logs. The bin indices are then scaled
+/// to `[0, 1]`. Categorical attributes are kept as-is.
+///
+/// Returns an [`AttributeError`] if any trace is missing one of the attributes, or
+/// it does not have the expected type (numeric after coercion for numeric, String
+/// for categorical attributes).
+///
+/// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+pub fn extract_trace_attribute_representations<B: Binner<f64>>(
+    log_1: &EventLog,
+    log_2: &EventLog,
+    attributes: &[TraceAttributeSpec],
+    binner_args: &B::Args,
+) -> AttributeResult<(Vec<ProjectedAttributes>, Vec<ProjectedAttributes>)> {
+    let numeric_data: Vec<(String, f64)> = log_1
+        .traces
+        .iter()
+        .chain(log_2.traces.iter())
+        .flat_map(|trace| {
+            attributes
+                .iter()
+                .filter(|spec| spec.kind == TraceAttributeKind::Numeric)
+                .map(|spec| {
+                    Ok((
+                        spec.key.clone(),
+                        trace.get_numeric_by_key(&spec.key, &spec.numeric_coercion)?,
+                    ))
+                })
+        })
+        .collect::<AttributeResult<_>>()?;
+    let binner_manager =
+        BinnerManager::<f64, B>::from_key_value_pairs(numeric_data, binner_args.clone());
+
+    let project_trace = |trace: &Trace| -> AttributeResult<ProjectedAttributes> {
+        attributes
+            .iter()
+            .map(|spec| {
+                Ok(match spec.kind {
+                    TraceAttributeKind::Numeric => {
+                        let value = trace.get_numeric_by_key(&spec.key, &spec.numeric_coercion)?;
+                        let bin = binner_manager.bin(&spec.key, value);
+                        let max_bin = binner_manager.num_bins(&spec.key).saturating_sub(1).max(1);
+                        ProjectedValue::Numeric(OrderedFloat(
+                            (bin as f64 / max_bin as f64).min(1.0),
+                        ))
+                    }
+                    TraceAttributeKind::Categorical => {
+                        ProjectedValue::Categorical(trace.get_string_by_key(&spec.key)?)
+                    }
+                })
+            })
+            .collect()
+    };
+
+    Ok((
+        log_1
+            .traces
+            .iter()
+            .map(project_trace)
+            .collect::<AttributeResult<_>>()?,
+        log_2
+            .traces
+            .iter()
+            .map(project_trace)
+            .collect::<AttributeResult<_>>()?,
+    ))
+}
+
+/// What to do if an event is missing a projected attribute.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum MissingValuePolicy {
+    /// Return an [`AttributeError`].
+    ///
+    /// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+    #[default]
+    Error,
+    /// Use [`ProjectedValue::Missing`] as a placeholder.
+    Placeholder,
+    /// Drop the event from the projected trace.
+    DropEvent,
+}
+
+/// How an event attribute is treated in a projection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum EventAttributeKind {
+    /// The attribute is a float. If `binned`, the values are binned per attribute,
+    /// otherwise they are min-max scaled. In both cases, the result lies in `[0, 1]`.
+    Numeric { binned: bool },
+    /// The attribute is a string, which is used as a category.
+    Categorical,
+}
+
+/// The specification of an event attribute in an [`EventAttributeProjection`].
+#[derive(Debug, Clone, Serialize)]
+pub struct EventAttributeSpec {
+    /// The key of the event attribute.
+    pub key: String,
+    /// How the attribute is treated.
+    pub kind: EventAttributeKind,
+    /// What to do if the attribute is missing.
+    pub missing: MissingValuePolicy,
+    /// Whether to coerce values of other types: For numeric attributes, all coercions
+    /// of [`NumericCoercion::lenient`] are applied. For categorical attributes, all
+    /// primitive values are converted to strings.
+    pub coerce: bool,
+}
+
+impl EventAttributeSpec {
+    /// A binned numeric attribute. Values are coerced leniently (see
+    /// [`NumericCoercion::lenient`]), and missing values result in an error.
+    pub fn numeric(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            kind: EventAttributeKind::Numeric { binned: true },
+            missing: MissingValuePolicy::default(),
+            coerce: true,
+        }
+    }
+
+    /// A categorical attribute. Other primitive values are converted to strings,
+    /// and missing values result in an error.
+    pub fn categorical(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            kind: EventAttributeKind::Categorical,
+            missing: MissingValuePolicy::default(),
+            coerce: true,
+        }
+    }
+
+    pub fn with_missing_value_policy(mut self, missing: MissingValuePolicy) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    pub fn with_coercion(mut self, coerce: bool) -> Self {
+        self.coerce = coerce;
+        self
+    }
+
+    /// Bin a numeric attribute (`true`) or min-max scale it (`false`). Has no effect
+    /// on categorical attributes.
+    pub fn with_binning(mut self, binned: bool) -> Self {
+        if let EventAttributeKind::Numeric { .. } = self.kind {
+            self.kind = EventAttributeKind::Numeric { binned };
+        }
+        self
+    }
+
+    /// Get the (unscaled) value of this attribute from an event.
+    ///
+    /// Returns `Ok(None)` if the attribute is missing and the event should be dropped.
+    fn extract(&self, event: &Event) -> AttributeResult<Option<ProjectedValue>> {
+        let attribute = match event.get_attribute_by_key(&self.key) {
+            Ok(attribute) => attribute,
+            Err(err) => {
+                return match self.missing {
+                    MissingValuePolicy::Error => Err(err),
+                    MissingValuePolicy::Placeholder => Ok(Some(ProjectedValue::Missing)),
+                    MissingValuePolicy::DropEvent => Ok(None),
+                }
+            }
+        };
+
+        let type_mismatch = |expected: &str| {
+            AttributeError::new(
+                AttributeLevel::Event,
+                &self.key,
+                AttributeErrorKind::TypeMismatch(expected.to_string(), attribute.value.clone()),
+            )
+        };
+
+        let value = match (self.kind, &attribute.value) {
+            (EventAttributeKind::Numeric { .. }, _) => {
+                let coercion = if self.coerce {
+                    NumericCoercion::lenient()
+                } else {
+                    NumericCoercion::strict()
+                };
+                let value = event.get_numeric_by_key(&self.key, &coercion)?;
+                ProjectedValue::Numeric(OrderedFloat(value))
+            }
+            (EventAttributeKind::Categorical, AttributeValue::String(value)) => {
+                ProjectedValue::Categorical(value.clone())
+            }
+            (EventAttributeKind::Categorical, value) if self.coerce => ProjectedValue::Categorical(
+                coerce_to_string(value)
+                    .ok_or_else(|| type_mismatch("String, Int, Float, Boolean, Date or ID"))?,
+            ),
+            (EventAttributeKind::Categorical, _) => return Err(type_mismatch("String")),
+        };
+        Ok(Some(value))
+    }
+}
+
+/// A configurable projection of events on a tuple of their attributes, e.g., the
+/// activity and the sales channel.
+#[derive(Debug, Clone, Serialize)]
+pub struct EventAttributeProjection {
+    pub attributes: Vec<EventAttributeSpec>,
+}
+
+impl EventAttributeProjection {
+    pub fn new(attributes: Vec<EventAttributeSpec>) -> Self {
+        Self { attributes }
+    }
+
+    /// Project the traces of two event logs on the configured event attributes.
+    ///
+    /// Numeric attributes are either binned using a binner of type `B` per attribute,
+    /// or min-max scaled. In both cases, the binners and the scaling are based on
+    /// the data of both event logs, and the resulting values lie in `[0, 1]`.
+    ///
+    /// Returns an [`AttributeError`] if an attribute is missing and its
+    /// [`MissingValuePolicy`] is [`MissingValuePolicy::Error`], or if an attribute
+    /// value has an unexpected type (after coercion).
+    ///
+    /// [`AttributeError`]: crate::utils::attributes::attribute_error::AttributeError
+    pub fn project_logs<B: Binner<f64>>(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+        binner_args: &B::Args,
+    ) -> AttributeResult<(Vec<ProjectedTrace>, Vec<ProjectedTrace>)> {
+        let mut traces_1 = self.project_log_unscaled(log_1)?;
+        let mut traces_2 = self.project_log_unscaled(log_2)?;
+
+        let numeric_data: Vec<(String, f64)> = traces_1
+            .iter()
+            .chain(traces_2.iter())
+            .flatten()
+            .flat_map(|event| {
+                self.attributes
+                    .iter()
+                    .zip(event.iter())
+                    .filter_map(|(spec, value)| match value {
+                        ProjectedValue::Numeric(value) => Some((spec.key.clone(), value.0)),
+                        _ => None,
+                    })
+            })
+            .collect();
+
+        let mut ranges: HashMap<String, (f64, f64)> = HashMap::new();
+        numeric_data.iter().for_each(|(key, value)| {
+            let range = ranges.entry(key.clone()).or_insert((*value, *value));
+            *range = (range.0.min(*value), range.1.max(*value));
+        });
+        let binner_manager =
+            BinnerManager::<f64, B>::from_key_value_pairs(numeric_data, binner_args.clone());
+
+        traces_1
+            .iter_mut()
+            .chain(traces_2.iter_mut())
+            .flatten()
+            .for_each(|event| {
+                self.attributes
+                    .iter()
+                    .zip(event.iter_mut())
+                    .for_each(|(spec, value)| {
+                        if let (
+                            EventAttributeKind::Numeric { binned },
+                            ProjectedValue::Numeric(value),
+                        ) = (spec.kind, value)
+                        {
+                            value.0 = if binned {
+                                let bin = binner_manager.bin(&spec.key, value.0);
+                                let max_bin =
+                                    binner_manager.num_bins(&spec.key).saturating_sub(1).max(1);
+                                (bin as f64 / max_bin as f64).min(1.0)
+                            } else {
+                                let (min, max) = ranges[&spec.key];
+                                if max > min {
+                                    (value.0 - min) / (max - min)
+                                } else {
+                                    0.0
+                                }
+                            };
+                        }
+                    })
+            });
+
+        Ok((traces_1, traces_2))
+    }
+
+    /// Project the events of each trace without scaling numeric values.
+    fn project_log_unscaled(&self, log: &EventLog) -> AttributeResult<Vec<ProjectedTrace>> {
+        log.traces
+            .iter()
+            .map(|trace| {
+                trace
+                    .events
+                    .iter()
+                    .map(|evt| {
+                        self.attributes
+                            .iter()
+                            .map(|spec| spec.extract(evt))
+                            .collect::<AttributeResult<Option<ProjectedAttributes>>>()
+                    })
+                    // Drop events where some attribute is missing and should be dropped
+                    .filter_map(Result::transpose)
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        binning::kmeans_binner::{KMeansArgs, KMeansBinner},
+        utils::{
+            attributes::add_or_overwrite_attribute,
+            constants::{ACTIVITY_KEY, START_TIMESTAMP_KEY},
+        },
+    };
+    use process_mining_macros::event_log;
+
+    #[test]
+    fn test_case_duration() {
+        let mut log = event_log!([a, b, c], [a]; base_timestamp=EPOCH);
+
+        assert_eq!(
+            extract_case_durations(&log, false).unwrap(),
+            vec![2.0 * 3600.0, 0.0]
+        );
+
+        // Atomic events, except for the first one which started half an hour before
+        // the trace's first completion
+        log.traces
+            .iter_mut()
+            .flat_map(|trace| trace.events.iter_mut())
+            .for_each(|evt| {
+                let timestamp = get_complete_timestamp(evt).unwrap();
+                add_or_overwrite_attribute(
+                    evt,
+                    START_TIMESTAMP_KEY,
+                    AttributeValue::Date(timestamp),
+                );
+            });
+        add_or_overwrite_attribute(
+            &mut log.traces[0].events[0],
+            START_TIMESTAMP_KEY,
+            AttributeValue::Date(DateTime::UNIX_EPOCH.fixed_offset() - TimeDelta::minutes(30)),
+        );
+        assert_eq!(
+            trace_to_case_duration(&log.traces[0], true).unwrap(),
+            2.5 * 3600.0
+        );
+        assert_eq!(trace_to_case_duration(&log.traces[1], true).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_waiting_time_trace() {
+        let mut log = event_log!([a, b, c]; base_timestamp=EPOCH);
+        let base = DateTime::UNIX_EPOCH.fixed_offset();
+
+        // a: [0:00, 0:00], b: [0:15, 1:00], c: [0:30, 2:00] (overlaps with b)
+        let start_timestamps = [
+            base,
+            base + TimeDelta::minutes(15),
+            base + TimeDelta::minutes(30),
+        ];
+        log.traces[0]
+            .events
+            .iter_mut()
+            .zip(start_timestamps)
+            .for_each(|(evt, start)| {
+                add_or_overwrite_attribute(evt, START_TIMESTAMP_KEY, AttributeValue::Date(start));
+            });
+
+        assert_eq!(
+            extract_waiting_time_traces(&log).unwrap(),
+            vec![vec![
+                ("a".to_string(), 0.0),
+                ("b".to_string(), 15.0 * 60.0),
+                ("c".to_string(), 0.0),
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_event_attribute_projection() {
+        let mut log_1 = event_log!([a, b], [a]);
+        let log_2 = event_log!([c]);
+
+        add_or_overwrite_attribute(
+            &mut log_1.traces[0].events[0],
+            "channel",
+            AttributeValue::String("web".into()),
+        );
+        add_or_overwrite_attribute(
+            &mut log_1.traces[0].events[0],
+            "amount",
+            AttributeValue::Int(10),
+        );
+        add_or_overwrite_attribute(
+            &mut log_1.traces[1].events[0],
+            "amount",
+            AttributeValue::Float(20.0),
+        );
+
+        let projection = EventAttributeProjection::new(vec![
+            EventAttributeSpec::categorical(ACTIVITY_KEY),
+            EventAttributeSpec::categorical("channel")
+                .with_missing_value_policy(MissingValuePolicy::Placeholder),
+            EventAttributeSpec::numeric("amount")
+                .with_binning(false)
+                .with_missing_value_policy(MissingValuePolicy::DropEvent),
+        ]);
+        let (traces_1, traces_2) = projection
+            .project_logs::<KMeansBinner>(&log_1, &log_2, &KMeansArgs::default())
+            .unwrap();
+
+        assert_eq!(
+            traces_1,
+            vec![
+                vec![vec![
+                    ProjectedValue::Categorical("a".into()),
+                    ProjectedValue::Categorical("web".into()),
+                    ProjectedValue::Numeric(OrderedFloat(0.0)),
+                ]],
+                vec![vec![
+                    ProjectedValue::Categorical("a".into()),
+                    ProjectedValue::Missing,
+                    ProjectedValue::Numeric(OrderedFloat(1.0)),
+                ]],
+            ]
+        );
+        assert_eq!(traces_2, vec![Vec::<ProjectedAttributes>::new()]);
+
+        // Without coercion, the Int amount is not accepted
+        let strict_projection =
+            EventAttributeProjection::new(vec![EventAttributeSpec::numeric("amount")
+                .with_coercion(false)
+                .with_missing_value_policy(MissingValuePolicy::DropEvent)]);
+        assert!(strict_projection
+            .project_logs::<KMeansBinner>(&log_1, &log_2, &KMeansArgs::default())
+            .is_err());
+    }
+}
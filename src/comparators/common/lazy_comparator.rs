@@ -0,0 +1,259 @@
+//! Lazily computed distance matrices for comparisons with very many variants, where
+//! the dense matrix of the distances between all pairs of variants does not fit
+//! into memory. The memoized distances are bounded (see [`DEFAULT_MEMO_LIMIT`]), so
+//! the memory usage does not grow with the number of variants.
+
+use std::{fmt::Debug, hash::Hash};
+
+use ndarray::Array2;
+use process_mining::EventLog;
+
+use crate::{
+    comparators::{
+        bootstrap::bootstrap_comparator::BootstrapTestComparator,
+        common::result::ComparatorParameters,
+        permutation_test::permutation_test_comparator::PermutationTestComparator,
+    },
+    distance::matrix::{DistanceMatrix, LazyDistanceMatrix},
+    utils::{attributes::attribute_error::AttributeResult, progress::ProgressReporter},
+};
+
+/// The default maximum number of memoized distances per distance matrix of a
+/// [`LazyComparator`], i.e., about 32 MiB of memoized distances.
+pub const DEFAULT_MEMO_LIMIT: usize = 1 << 20;
+
+/// A comparator that uses a [`LazyDistanceMatrix`] instead of a dense distance
+/// matrix: Distances between variants are only computed (and memoized) when they
+/// are needed, e.g., for the variants occurring in a permutation. Behaves exactly
+/// like the wrapped comparator otherwise, including its name and parameters in the
+/// [`ComparisonMetadata`].
+///
+/// [`ComparisonMetadata`]: super::result::ComparisonMetadata
+#[derive(Debug, Clone)]
+pub struct LazyComparator<C> {
+    comparator: C,
+    memo_limit: usize,
+}
+
+impl<C> LazyComparator<C> {
+    /// Wrap `comparator`, memoizing at most [`DEFAULT_MEMO_LIMIT`] distances per
+    /// distance matrix.
+    pub fn new(comparator: C) -> Self {
+        Self {
+            comparator,
+            memo_limit: DEFAULT_MEMO_LIMIT,
+        }
+    }
+
+    /// Memoize at most `memo_limit` distances per distance matrix, bounding the
+    /// memory usage. Further distances are recomputed whenever they are needed.
+    pub fn with_memo_limit(mut self, memo_limit: usize) -> Self {
+        self.memo_limit = memo_limit;
+        self
+    }
+
+    pub fn into_inner(self) -> C {
+        self.comparator
+    }
+
+    /// The lazy matrix of the distances between all pairs of `variants`.
+    fn symmetric_matrix<'a, T>(&'a self, variants: &[T]) -> LazyDistanceMatrix<'a, T>
+    where
+        T: Hash + Eq + Clone + Ord + Debug,
+        C: PermutationTestComparator<T>,
+    {
+        LazyDistanceMatrix::symmetric(
+            variants.to_vec(),
+            Box::new(|rep_1, rep_2| self.comparator.cost(rep_1, rep_2)),
+        )
+        .with_memo_limit(self.memo_limit)
+    }
+}
+
+impl<T, C> PermutationTestComparator<T> for LazyComparator<C>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: PermutationTestComparator<T>,
+{
+    fn cost(&self, rep_1: &T, rep_2: &T) -> f64 {
+        self.comparator.cost(rep_1, rep_2)
+    }
+
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<T>, Vec<T>)> {
+        self.comparator.extract_representations(log_1, log_2)
+    }
+
+    fn name(&self) -> String {
+        self.comparator.name()
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        self.comparator.parameters()
+    }
+
+    fn progress_reporter(&self) -> &dyn ProgressReporter {
+        self.comparator.progress_reporter()
+    }
+
+    fn variant_distance_matrix<'a>(
+        &'a self,
+        variants: &[T],
+        _reporter: &dyn ProgressReporter,
+    ) -> Box<dyn DistanceMatrix + 'a>
+    where
+        T: 'a,
+    {
+        Box::new(self.symmetric_matrix(variants))
+    }
+
+    fn compute_symmetric_distance_matrix(
+        &self,
+        variants: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Array2<f64> {
+        self.comparator
+            .compute_symmetric_distance_matrix(variants, reporter)
+    }
+}
+
+impl<T, C> BootstrapTestComparator<T> for LazyComparator<C>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: BootstrapTestComparator<T>,
+{
+    fn cost(&self, rep_1: &T, rep_2: &T) -> f64 {
+        self.comparator.cost(rep_1, rep_2)
+    }
+
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<T>, Vec<T>)> {
+        self.comparator.extract_representations(log_1, log_2)
+    }
+
+    fn name(&self) -> String {
+        self.comparator.name()
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        self.comparator.parameters()
+    }
+
+    fn progress_reporter(&self) -> &dyn ProgressReporter {
+        self.comparator.progress_reporter()
+    }
+
+    fn distance_matrix<'a>(
+        &'a self,
+        variants_1: &[T],
+        variants_2: &[T],
+        _reporter: &dyn ProgressReporter,
+    ) -> Box<dyn DistanceMatrix + 'a>
+    where
+        T: 'a,
+    {
+        Box::new(
+            LazyDistanceMatrix::new(
+                variants_1.to_vec(),
+                variants_2.to_vec(),
+                Box::new(|rep_1, rep_2| self.comparator.cost(rep_1, rep_2)),
+            )
+            .with_memo_limit(self.memo_limit),
+        )
+    }
+
+    fn compute_distance_matrix(
+        &self,
+        variants_1: &[T],
+        variants_2: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Array2<f64> {
+        self.comparator
+            .compute_distance_matrix(variants_1, variants_2, reporter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comparators::{
+            bootstrap::control_flow::ControlFlowBootstrapComparator,
+            permutation_test::{
+                control_flow::ControlFlowPermutationComparator,
+                permutation_test_comparator::compute_permutation_test_distribution,
+            },
+        },
+        utils::progress::SilentReporter,
+    };
+    use process_mining_macros::event_log;
+
+    #[test]
+    fn test_lazy_comparators_match_dense() {
+        let log_1 = event_log!([a, b], [a, c], [a, b, c], [a, b]; base_timestamp=EPOCH);
+        let log_2 = event_log!([a, b], [a, d], [a, c, b], [d]; base_timestamp=EPOCH);
+
+        let dense = ControlFlowPermutationComparator
+            .compare(&log_1, &log_2, 100, Some(7))
+            .unwrap();
+        let lazy = LazyComparator::new(ControlFlowPermutationComparator)
+            .with_memo_limit(4)
+            .compare(&log_1, &log_2, 100, Some(7))
+            .unwrap();
+        assert_eq!(lazy.logs_emd, dense.logs_emd);
+        assert_eq!(lazy.permutation_emds, dense.permutation_emds);
+
+        let dense = ControlFlowBootstrapComparator
+            .compare(&log_1, &log_2, 3, 100, Some(7))
+            .unwrap();
+        let lazy = LazyComparator::new(ControlFlowBootstrapComparator)
+            .compare(&log_1, &log_2, 3, 100, Some(7))
+            .unwrap();
+        assert_eq!(lazy.logs_emd, dense.logs_emd);
+        assert_eq!(lazy.bootstrap_emds, dense.bootstrap_emds);
+    }
+
+    #[test]
+    fn test_memo_is_bounded() {
+        let log_1 = event_log!([a], [a, b], [a, b, c], [a, c], [b]; base_timestamp=EPOCH);
+        let log_2 = event_log!([c], [c, b], [c, b, a], [b, a], [a, b, b]; base_timestamp=EPOCH);
+        let comparator = LazyComparator::new(ControlFlowPermutationComparator).with_memo_limit(10);
+        let (behavior_1, behavior_2) =
+            PermutationTestComparator::extract_representations(&comparator, &log_1, &log_2)
+                .unwrap();
+        let mut variants: Vec<_> = behavior_1.iter().chain(&behavior_2).cloned().collect();
+        variants.sort();
+        variants.dedup();
+
+        // The permutation distribution of `compare` accesses the distances between
+        // all 10 variants, i.e., far more than 10 distinct distances.
+        let matrix = comparator.symmetric_matrix(&variants);
+        let permutation_emds = compute_permutation_test_distribution(
+            &matrix,
+            variants,
+            behavior_1,
+            behavior_2,
+            None,
+            100,
+            Some(7),
+            &SilentReporter,
+        );
+        assert_eq!(matrix.num_memoized(), 10);
+
+        let dense = ControlFlowPermutationComparator
+            .compare(&log_1, &log_2, 100, Some(7))
+            .unwrap();
+        for (lazy, dense) in permutation_emds.iter().zip(&dense.permutation_emds) {
+            assert!((lazy - dense).abs() < 1e-12);
+        }
+
+        let comparator = LazyComparator::new(ControlFlowPermutationComparator);
+        assert_eq!(comparator.memo_limit, DEFAULT_MEMO_LIMIT);
+    }
+}
@@ -1,8 +1,21 @@
-//! Methods that are shared between the different tests. Includes methods for event
-//! log preparation (before testing), common methods for representation extraction
-//! (traces and service time traces) and commont functions for handling stochastic
-//! languages.
+//! Methods that are shared between the different tests:
+//!
+//! - [`approximation`]: Approximate comparisons for exploratory analyses
+//! - [`comparison_error`]: Errors of comparisons of several event logs or related traces
+//! - [`distance_cache`]: Caching of distances between variants
+//! - [`extraction`]: Representation extraction (traces and service time traces)
+//! - [`lazy_comparator`]: Lazily computed distances for very large sets of variants
+//! - [`preparation`]: Event log preparation (before testing)
+//! - [`result`]: Metadata and export of comparison results
+//! - [`stochastic_language`]: Handling of stochastic languages
+//! - [`with_progress`]: Configuration of progress reporting
 
+pub mod approximation;
+pub mod comparison_error;
+pub mod distance_cache;
 pub mod extraction;
+pub mod lazy_comparator;
 pub mod preparation;
+pub mod result;
 pub mod stochastic_language;
+pub mod with_progress;
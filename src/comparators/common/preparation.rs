@@ -222,6 +222,23 @@ pub fn ensure_start_timestamp_key(log: &mut EventLog) -> AttributeResult<()> {
     fold_instance_id_to_start_timestamps_log(log)
 }
 
+/// Create an event log with the given traces and the log-level information
+/// (attributes, extensions, classifiers, global attributes) of `header`.
+///
+/// Useful to extract the representations of traces from several event logs at
+/// once, e.g., to pool event logs, or of a single event log (with no traces in
+/// the second event log).
+pub fn log_with_traces(header: &EventLog, traces: Vec<Trace>) -> EventLog {
+    EventLog {
+        attributes: header.attributes.clone(),
+        traces,
+        extensions: header.extensions.clone(),
+        classifiers: header.classifiers.clone(),
+        global_trace_attrs: header.global_trace_attrs.clone(),
+        global_event_attrs: header.global_event_attrs.clone(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::utils::{attributes::get_start_timestamp, constants::ACTIVITY_KEY};
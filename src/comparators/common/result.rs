@@ -0,0 +1,255 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::stats::{
+    effect_size::EffectSize, pvalue::PValueEstimate, sequential::SequentialSummary,
+};
+
+/// The version of this crate, recorded in the [`ComparisonMetadata`].
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The parameters of a comparator recorded in the [`ComparisonMetadata`], e.g., the
+/// binner arguments.
+pub type ComparatorParameters = BTreeMap<String, serde_json::Value>;
+
+/// The parameters of a comparator derived from its serializable configuration: One
+/// entry per field. Configurations that do not serialize to a map (e.g., unit
+/// structs) have no parameters.
+pub fn config_parameters(config: &impl Serialize) -> ComparatorParameters {
+    match serde_json::to_value(config) {
+        Ok(serde_json::Value::Object(fields)) => fields.into_iter().collect(),
+        _ => ComparatorParameters::new(),
+    }
+}
+
+/// Information about how a comparison result was obtained.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparisonMetadata {
+    /// The name of the comparator, by default its (unqualified) type name.
+    pub comparator: String,
+    /// The parameters of the comparator, e.g., the binner arguments.
+    pub parameters: ComparatorParameters,
+    /// The seed used for the random sampling, if any.
+    pub seed: Option<u64>,
+    /// The number of cases in the first event log.
+    pub log_1_size: usize,
+    /// The number of cases in the second event log.
+    pub log_2_size: usize,
+    /// The number of distinct representations (variants) in the first event log.
+    pub log_1_variants: usize,
+    /// The number of distinct representations (variants) in the second event log.
+    pub log_2_variants: usize,
+    /// The number of EMDs computed for the test distribution.
+    pub distribution_size: usize,
+    /// The size of the bootstrap samples. `None` for the permutation test.
+    pub resample_size: Option<usize>,
+    /// The time taken for the comparison in seconds.
+    pub runtime_seconds: f64,
+    /// The version of this crate used for the comparison.
+    pub crate_version: String,
+}
+
+/// An error that can occur when exporting or importing a comparison result.
+#[derive(Debug, Error)]
+pub enum ResultExportError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+}
+
+/// Common functionality of the results of the different tests.
+pub trait ComparisonResult: Serialize + DeserializeOwned {
+    /// The EMD measured between the two event logs.
+    fn logs_emd(&self) -> f64;
+
+    /// The EMDs of the test distribution (permutation or bootstrap).
+    fn distribution(&self) -> &[f64];
+
+    /// The p-value `(b + 1) / (m + 1)`, where `b` of the `m` EMDs in the test
+    /// distribution exceed the observed EMD.
+    fn pvalue(&self) -> f64;
+
+    /// The p-value estimate including its Monte Carlo error.
+    fn pvalue_estimate(&self) -> &PValueEstimate;
+
+    /// Effect sizes of the observed EMD relative to the test distribution.
+    fn effect_size(&self) -> &EffectSize;
+
+    /// Information about how the result was obtained.
+    fn metadata(&self) -> &ComparisonMetadata;
+
+    /// The summary of the sequential test, if the comparison was run sequentially.
+    fn sequential(&self) -> Option<&SequentialSummary>;
+
+    /// Serialize the result (including metadata and distribution) to a JSON string.
+    fn to_json(&self) -> Result<String, ResultExportError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Deserialize a result from a JSON string created with [`to_json`](ComparisonResult::to_json).
+    fn from_json(json: &str) -> Result<Self, ResultExportError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Write the result as JSON to a file.
+    fn write_json(&self, path: impl AsRef<Path>) -> Result<(), ResultExportError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read a result from a JSON file created with [`write_json`](ComparisonResult::write_json).
+    fn read_json(path: impl AsRef<Path>) -> Result<Self, ResultExportError> {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+
+    /// Write the EMDs of the test distribution as CSV with the columns `sample`
+    /// (the index in the distribution) and `emd`.
+    fn write_distribution_csv(&self, writer: impl Write) -> Result<(), ResultExportError> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(["sample", "emd"])?;
+        for (index, emd) in self.distribution().iter().enumerate() {
+            writer.write_record([index.to_string(), emd.to_string()])?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Read a test distribution written by [`ComparisonResult::write_distribution_csv`].
+pub fn read_distribution_csv(reader: impl Read) -> Result<Vec<f64>, ResultExportError> {
+    csv::Reader::from_reader(reader)
+        .deserialize::<(usize, f64)>()
+        .map(|row| Ok(row?.1))
+        .collect()
+}
+
+/// Shorten a (fully qualified) type name as returned by [`std::any::type_name`]
+/// by removing the module paths, including those of generic arguments.
+///
+/// E.g., `pcomprs::A<pcomprs::binning::B>` becomes `A<B>`.
+pub fn short_type_name(type_name: &str) -> String {
+    let mut result = String::with_capacity(type_name.len());
+    let mut path = String::new();
+    for c in type_name.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            path.push(c);
+        } else {
+            result.push_str(path.rsplit("::").next().unwrap_or_default());
+            path.clear();
+            result.push(c);
+        }
+    }
+    result.push_str(path.rsplit("::").next().unwrap_or_default());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        binning::kmeans_binner::KMeansArgs,
+        comparators::permutation_test::{
+            permutation_test_comparator::{
+                PermutationTestComparator, PermutationTestComparisonResult,
+            },
+            timed_levenshtein::TimedLevenshteinPermutationComparator,
+        },
+    };
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(
+            short_type_name("pcomprs::A<pcomprs::binning::B, (u8, alloc::string::String)>"),
+            "A<B, (u8, String)>"
+        );
+        assert_eq!(short_type_name("Plain"), "Plain");
+    }
+
+    fn example_result() -> PermutationTestComparisonResult {
+        PermutationTestComparisonResult {
+            logs_emd: 0.5,
+            permutation_emds: vec![0.25, 0.75],
+            pvalue: 0.5,
+            pvalue_estimate: PValueEstimate::from_distribution(
+                &[0.25, 0.75],
+                0.5,
+                &Default::default(),
+            ),
+            effect_size: EffectSize::from_distribution(&[0.25, 0.75], 0.5),
+            metadata: ComparisonMetadata {
+                comparator: "ControlFlowPermutationComparator".into(),
+                parameters: ComparatorParameters::from([("k".into(), 3.into())]),
+                seed: Some(1337),
+                log_1_size: 10,
+                log_2_size: 12,
+                log_1_variants: 3,
+                log_2_variants: 4,
+                distribution_size: 2,
+                resample_size: None,
+                runtime_seconds: 0.1,
+                crate_version: CRATE_VERSION.into(),
+            },
+            sequential: None,
+        }
+    }
+
+    #[test]
+    fn test_json_and_csv_roundtrip() {
+        let result = example_result();
+
+        let reloaded =
+            PermutationTestComparisonResult::from_json(&result.to_json().unwrap()).unwrap();
+        assert_eq!(reloaded.permutation_emds, result.permutation_emds);
+        assert_eq!(reloaded.metadata, result.metadata);
+        assert_eq!(reloaded.pvalue_estimate, result.pvalue_estimate);
+
+        let mut csv = Vec::new();
+        result.write_distribution_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv.clone()).unwrap(),
+            "sample,emd\n0,0.25\n1,0.75\n"
+        );
+        assert_eq!(
+            read_distribution_csv(csv.as_slice()).unwrap(),
+            result.permutation_emds
+        );
+    }
+
+    #[test]
+    fn test_json_roundtrip_of_nan() {
+        // E.g., the EMD of two logs without representations
+        let mut result = example_result();
+        result.logs_emd = f64::NAN;
+        result.permutation_emds = vec![f64::NAN, 0.5];
+
+        let reloaded =
+            PermutationTestComparisonResult::from_json(&result.to_json().unwrap()).unwrap();
+        assert!(reloaded.logs_emd.is_nan());
+        assert!(reloaded.permutation_emds[0].is_nan());
+        assert_eq!(reloaded.permutation_emds[1], 0.5);
+    }
+
+    #[test]
+    fn test_config_parameters() {
+        let comparator = TimedLevenshteinPermutationComparator::new(KMeansArgs::default());
+        assert_eq!(
+            PermutationTestComparator::parameters(&comparator),
+            ComparatorParameters::from([(
+                "binner_args".to_string(),
+                serde_json::json!({ "k": 3, "max_iter": 100, "seed": null })
+            )])
+        );
+    }
+}
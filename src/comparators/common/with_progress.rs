@@ -0,0 +1,205 @@
+//! Configuring how comparators report their progress, e.g., to silence the progress
+//! bars in services and tests.
+
+use std::{fmt::Debug, hash::Hash};
+
+use ndarray::Array2;
+use process_mining::EventLog;
+
+use crate::{
+    comparators::{
+        bootstrap::bootstrap_comparator::BootstrapTestComparator,
+        common::result::ComparatorParameters,
+        permutation_test::permutation_test_comparator::PermutationTestComparator,
+    },
+    distance::matrix::DistanceMatrix,
+    utils::{
+        attributes::attribute_error::AttributeResult,
+        progress::{ProgressReporter, SilentReporter},
+    },
+};
+
+/// A comparator reporting its progress to the given [`ProgressReporter`] instead of
+/// showing progress bars on stderr. Behaves exactly like the wrapped comparator
+/// otherwise.
+#[derive(Debug, Clone)]
+pub struct WithProgress<C, R> {
+    comparator: C,
+    reporter: R,
+}
+
+impl<C, R: ProgressReporter> WithProgress<C, R> {
+    pub fn new(comparator: C, reporter: R) -> Self {
+        Self {
+            comparator,
+            reporter,
+        }
+    }
+
+    pub fn into_inner(self) -> C {
+        self.comparator
+    }
+}
+
+impl<C> WithProgress<C, SilentReporter> {
+    /// Do not report any progress.
+    pub fn silent(comparator: C) -> Self {
+        Self::new(comparator, SilentReporter)
+    }
+}
+
+impl<T, C, R> PermutationTestComparator<T> for WithProgress<C, R>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: PermutationTestComparator<T>,
+    R: ProgressReporter,
+{
+    fn cost(&self, rep_1: &T, rep_2: &T) -> f64 {
+        self.comparator.cost(rep_1, rep_2)
+    }
+
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<T>, Vec<T>)> {
+        self.comparator.extract_representations(log_1, log_2)
+    }
+
+    fn name(&self) -> String {
+        self.comparator.name()
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        self.comparator.parameters()
+    }
+
+    fn progress_reporter(&self) -> &dyn ProgressReporter {
+        &self.reporter
+    }
+
+    fn variant_distance_matrix<'a>(
+        &'a self,
+        variants: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Box<dyn DistanceMatrix + 'a>
+    where
+        T: 'a,
+    {
+        self.comparator.variant_distance_matrix(variants, reporter)
+    }
+
+    fn compute_symmetric_distance_matrix(
+        &self,
+        variants: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Array2<f64> {
+        self.comparator
+            .compute_symmetric_distance_matrix(variants, reporter)
+    }
+}
+
+impl<T, C, R> BootstrapTestComparator<T> for WithProgress<C, R>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: BootstrapTestComparator<T>,
+    R: ProgressReporter,
+{
+    fn cost(&self, rep_1: &T, rep_2: &T) -> f64 {
+        self.comparator.cost(rep_1, rep_2)
+    }
+
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<T>, Vec<T>)> {
+        self.comparator.extract_representations(log_1, log_2)
+    }
+
+    fn name(&self) -> String {
+        self.comparator.name()
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        self.comparator.parameters()
+    }
+
+    fn progress_reporter(&self) -> &dyn ProgressReporter {
+        &self.reporter
+    }
+
+    fn distance_matrix<'a>(
+        &'a self,
+        variants_1: &[T],
+        variants_2: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Box<dyn DistanceMatrix + 'a>
+    where
+        T: 'a,
+    {
+        self.comparator
+            .distance_matrix(variants_1, variants_2, reporter)
+    }
+
+    fn compute_distance_matrix(
+        &self,
+        variants_1: &[T],
+        variants_2: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Array2<f64> {
+        self.comparator
+            .compute_distance_matrix(variants_1, variants_2, reporter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comparators::{
+            common::distance_cache::{CachedComparator, DistanceCache},
+            permutation_test::control_flow::ControlFlowPermutationComparator,
+        },
+        utils::progress::{CallbackReporter, ProgressEvent, ProgressEventKind},
+    };
+    use process_mining_macros::event_log;
+    use std::cell::RefCell;
+
+    fn finished_totals(events: &RefCell<Vec<ProgressEvent>>) -> Vec<u64> {
+        events
+            .borrow()
+            .iter()
+            .filter(|event| event.kind == ProgressEventKind::Finished)
+            .map(|event| event.total)
+            .collect()
+    }
+
+    #[test]
+    fn test_progress_is_reported_to_reporter() {
+        let log_1 = event_log!([a, b], [a, c]; base_timestamp=EPOCH);
+        let log_2 = event_log!([a, b], [a, d]; base_timestamp=EPOCH);
+
+        // Regardless of the order of the wrappers, the cached distance matrix and the
+        // permutation distribution are reported
+        let events: RefCell<Vec<ProgressEvent>> = RefCell::new(Vec::new());
+        let reporter = CallbackReporter::new(|event| events.borrow_mut().push(event));
+        let comparator = CachedComparator::new(
+            WithProgress::new(ControlFlowPermutationComparator, reporter),
+            DistanceCache::shared(),
+        )
+        .with_cache_key("control_flow");
+        comparator.compare(&log_1, &log_2, 10, Some(0)).unwrap();
+        assert_eq!(finished_totals(&events), vec![9, 10]);
+
+        let events: RefCell<Vec<ProgressEvent>> = RefCell::new(Vec::new());
+        let reporter = CallbackReporter::new(|event| events.borrow_mut().push(event));
+        let comparator = WithProgress::new(
+            CachedComparator::new(ControlFlowPermutationComparator, DistanceCache::shared())
+                .with_cache_key("control_flow"),
+            reporter,
+        );
+        comparator.compare(&log_1, &log_2, 10, Some(0)).unwrap();
+        assert_eq!(finished_totals(&events), vec![9, 10]);
+    }
+}
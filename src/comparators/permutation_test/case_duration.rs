@@ -0,0 +1,90 @@
+use process_mining::EventLog;
+use serde::Serialize;
+
+use crate::{
+    binning::{
+        kmeans_binner::{KMeansArgs, KMeansBinner},
+        Binner,
+    },
+    comparators::common::{
+        extraction::extract_case_duration_representations,
+        result::{config_parameters, ComparatorParameters},
+    },
+    utils::{attributes::attribute_error::AttributeResult, ordered_float::OrderedFloat},
+};
+
+use super::permutation_test_comparator::PermutationTestComparator;
+
+/// An implementation of the [`PermutationTestComparator`] for comparing the _case
+/// durations_ (throughput times) of two event logs, i.e., the time from the first
+/// to the last `time:timestamp` of each case.
+///
+/// The case durations are either binned using a [`Binner`] trained on both event
+/// logs (by default, K-Means++ clustering), or compared as continuous values. In both
+/// cases, the absolute difference is used as a distance notion between cases.
+#[derive(Debug, Serialize)]
+#[serde(bound(serialize = "B::Args: Serialize"))]
+pub struct CaseDurationPermutationComparator<B: Binner<f64> = KMeansBinner> {
+    binner_args: Option<B::Args>,
+    consider_start_timestamp: bool,
+}
+
+impl Default for CaseDurationPermutationComparator {
+    fn default() -> Self {
+        Self::new(KMeansArgs::default())
+    }
+}
+
+impl CaseDurationPermutationComparator {
+    /// Compare the case durations as continuous values (in seconds) without binning.
+    pub fn continuous() -> Self {
+        Self {
+            binner_args: None,
+            consider_start_timestamp: false,
+        }
+    }
+}
+
+impl<B: Binner<f64>> CaseDurationPermutationComparator<B> {
+    /// Compare the case durations binned using a binner of type `B`.
+    pub fn new(binner_args: B::Args) -> Self {
+        Self {
+            binner_args: Some(binner_args),
+            consider_start_timestamp: false,
+        }
+    }
+
+    /// Also consider the `start_timestamp` of events, so that a case starts with
+    /// the earliest start of any of its events.
+    pub fn with_start_timestamp(mut self, consider_start_timestamp: bool) -> Self {
+        self.consider_start_timestamp = consider_start_timestamp;
+        self
+    }
+}
+
+impl<B: Binner<f64>> PermutationTestComparator<OrderedFloat>
+    for CaseDurationPermutationComparator<B>
+where
+    B::Args: Serialize,
+{
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<OrderedFloat>, Vec<OrderedFloat>)> {
+        extract_case_duration_representations::<B>(
+            log_1,
+            log_2,
+            self.consider_start_timestamp,
+            self.binner_args.as_ref(),
+        )
+    }
+
+    fn cost(&self, rep_1: &OrderedFloat, rep_2: &OrderedFloat) -> f64 {
+        (rep_1.0 - rep_2.0).abs()
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        config_parameters(self)
+    }
+}
@@ -0,0 +1,100 @@
+use process_mining::EventLog;
+use serde::Serialize;
+
+use crate::{
+    binning::{
+        kmeans_binner::{KMeansArgs, KMeansBinner},
+        Binner,
+    },
+    comparators::common::{
+        extraction::EventAttributeProjection,
+        result::{config_parameters, ComparatorParameters},
+    },
+    distance::weighted_levenshtein::postnormalized_weighted_levenshtein_distance,
+    representation::ProjectedTrace,
+    utils::attributes::attribute_error::AttributeResult,
+};
+
+use super::permutation_test_comparator::PermutationTestComparator;
+
+/// An implementation of the [`PermutationTestComparator`] for comparisons of traces projected
+/// on arbitrary event attributes (see [`EventAttributeProjection`]), e.g.,
+/// (activity, channel) sequences. Numeric attributes are binned using a binner
+/// of type `B` (by default, K-Means++ clustering) or min-max scaled. Uses the
+/// postnormalized weighted Levenshtein distance as a distance notion between the
+/// projected traces.
+#[derive(Debug, Serialize)]
+#[serde(bound(serialize = "B::Args: Serialize"))]
+pub struct EventAttributePermutationComparator<B: Binner<f64> = KMeansBinner> {
+    projection: EventAttributeProjection,
+    binner_args: B::Args,
+}
+
+impl EventAttributePermutationComparator {
+    /// Compare the given projection, binning numeric attributes with K-Means++
+    /// clustering using default arguments.
+    pub fn new(projection: EventAttributeProjection) -> Self {
+        Self::with_binner_args(projection, KMeansArgs::default())
+    }
+}
+
+impl<B: Binner<f64>> EventAttributePermutationComparator<B> {
+    /// Compare the given projection, binning numeric attributes with a binner of
+    /// type `B`.
+    pub fn with_binner_args(projection: EventAttributeProjection, binner_args: B::Args) -> Self {
+        Self {
+            projection,
+            binner_args,
+        }
+    }
+}
+
+impl<B: Binner<f64>> PermutationTestComparator<ProjectedTrace>
+    for EventAttributePermutationComparator<B>
+where
+    B::Args: Serialize,
+{
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<ProjectedTrace>, Vec<ProjectedTrace>)> {
+        self.projection
+            .project_logs::<B>(log_1, log_2, &self.binner_args)
+    }
+
+    fn cost(&self, rep_1: &ProjectedTrace, rep_2: &ProjectedTrace) -> f64 {
+        postnormalized_weighted_levenshtein_distance(rep_1, rep_2)
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        config_parameters(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comparators::common::extraction::{EventAttributeSpec, MissingValuePolicy};
+    use process_mining_macros::event_log;
+
+    #[test]
+    fn test_traces_without_projected_events() {
+        // No event has a `channel` attribute, so all events are dropped
+        let log_1 = event_log!([a, b], [a, c]; base_timestamp=EPOCH);
+        let log_2 = event_log!([a, b], [a]; base_timestamp=EPOCH);
+        let comparator =
+            EventAttributePermutationComparator::new(EventAttributeProjection::new(vec![
+                EventAttributeSpec::categorical("channel")
+                    .with_missing_value_policy(MissingValuePolicy::DropEvent),
+            ]));
+
+        let (behavior_1, _) = comparator.extract_representations(&log_1, &log_2).unwrap();
+        assert!(behavior_1.iter().all(Vec::is_empty));
+        assert_eq!(comparator.cost(&behavior_1[0], &behavior_1[1]), 0.0);
+
+        let result = comparator.compare(&log_1, &log_2, 20, Some(0)).unwrap();
+        assert_eq!(result.logs_emd, 0.0);
+        assert!(result.permutation_emds.iter().all(|emd| *emd == 0.0));
+    }
+}
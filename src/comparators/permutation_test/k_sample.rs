@@ -0,0 +1,312 @@
+//! The k-sample permutation test: Test whether several event logs (e.g., of the
+//! branches of a company) all stem from the same process.
+//!
+//! All event logs are pooled, and the test statistic (see [`KSampleStatistic`])
+//! is compared to its distribution under random reassignments of the cases to the
+//! event logs (i.e., permutations of the group labels).
+
+use std::{fmt::Debug, hash::Hash, time::Instant};
+
+use ndarray::Array2;
+use process_mining::EventLog;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    comparators::common::{
+        comparison_error::ComparisonError,
+        result::{ComparisonMetadata, ComparisonResult, CRATE_VERSION},
+        stochastic_language::StochasticLanguage,
+    },
+    distance::matrix::DistanceMatrix,
+    pairwise::{
+        language_emd, pairwise_language_emds, pool_variants, split_languages, PooledVariants,
+    },
+    stats::{
+        effect_size::EffectSize,
+        pvalue::{PValueEstimate, PValueOptions},
+        sequential::SequentialSummary,
+    },
+    utils::nullable_float,
+};
+
+use super::permutation_test_comparator::PermutationTestComparator;
+
+/// The test statistic of the k-sample permutation test.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KSampleStatistic {
+    /// The sum of the EMDs between all pairs of event logs.
+    #[default]
+    SumOfPairwiseEmds,
+    /// The sum of the EMDs between each event log and the pooled event logs, weighted
+    /// by the fraction of cases in the event log. Requires only `k` EMDs per
+    /// permutation instead of `k(k-1)/2`.
+    EmdToPooled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KSampleComparisonResult {
+    /// The test statistic measured on the original event logs.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub statistic: f64,
+    /// The kind of test statistic.
+    pub statistic_kind: KSampleStatistic,
+    /// The test statistics computed in the permutation phase.
+    #[serde(deserialize_with = "nullable_float::deserialize_vec")]
+    pub permutation_statistics: Vec<f64>,
+    /// The p-value `(b + 1) / (m + 1)`, where `b` of the `m` permutation statistics
+    /// exceed the observed statistic. See `pvalue_estimate` for the raw fraction
+    /// `b / m` and the confidence interval.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub pvalue: f64,
+    /// The p-value estimate including its Monte Carlo error.
+    pub pvalue_estimate: PValueEstimate,
+    /// Effect sizes of the observed statistic relative to the test distribution.
+    pub effect_size: EffectSize,
+    /// The EMDs between each pair of the original event logs, in the order of the
+    /// event logs passed to [`compare_k_samples`]. Symmetric, with a zero diagonal.
+    pub pairwise_emds: Array2<f64>,
+    /// The number of cases in each event log.
+    pub log_sizes: Vec<usize>,
+    /// The number of variants (distinct representations) in each event log.
+    pub log_variants: Vec<usize>,
+    /// Information about how the result was obtained. The log sizes and variants
+    /// are those of the first two event logs; See `log_sizes` and `log_variants`
+    /// for all event logs.
+    pub metadata: ComparisonMetadata,
+}
+
+impl ComparisonResult for KSampleComparisonResult {
+    /// The test statistic measured on the original event logs.
+    fn logs_emd(&self) -> f64 {
+        self.statistic
+    }
+
+    fn distribution(&self) -> &[f64] {
+        &self.permutation_statistics
+    }
+
+    fn pvalue(&self) -> f64 {
+        self.pvalue
+    }
+
+    fn pvalue_estimate(&self) -> &PValueEstimate {
+        &self.pvalue_estimate
+    }
+
+    fn effect_size(&self) -> &EffectSize {
+        &self.effect_size
+    }
+
+    fn metadata(&self) -> &ComparisonMetadata {
+        &self.metadata
+    }
+
+    fn sequential(&self) -> Option<&SequentialSummary> {
+        None
+    }
+}
+
+/// Test whether all `logs` stem from the same process using the k-sample permutation
+/// test with `distribution_size` permutations.
+///
+/// The representations of the traces are extracted from the pooled event logs, so
+/// that, e.g., the binning of service times is consistent across the event logs.
+///
+/// Returns an `Err` if the comparator fails to extract the representations (see
+/// [`PermutationTestComparator::compare`]), if it does not extract one representation
+/// per trace, if an event log has no traces, or if fewer than two event logs are
+/// passed.
+pub fn compare_k_samples<T, C>(
+    comparator: &C,
+    logs: &[&EventLog],
+    distribution_size: usize,
+    statistic: KSampleStatistic,
+    pvalue_options: &PValueOptions,
+    seed: Option<u64>,
+) -> Result<KSampleComparisonResult, ComparisonError>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: PermutationTestComparator<T> + ?Sized,
+{
+    if logs.len() < 2 {
+        return Err(ComparisonError::TooFewLogs { found: logs.len() });
+    }
+    let start = Instant::now();
+
+    let PooledVariants {
+        variant_distances,
+        population,
+        log_sizes,
+    } = pool_variants(comparator, logs)?;
+    let group_statistics = GroupStatistics::new(
+        variant_distances.distances.as_ref(),
+        &population,
+        &log_sizes,
+        statistic,
+    );
+
+    let groups = group_statistics.groups(&population);
+    let log_variants: Vec<usize> = groups.iter().map(|group| group.variants.len()).collect();
+    let pairwise_emds = pairwise_language_emds(variant_distances.distances.as_ref(), &groups);
+    let observed = group_statistics.statistic(&groups);
+
+    let mut rng = if let Some(s) = seed {
+        StdRng::seed_from_u64(s)
+    } else {
+        StdRng::from_entropy()
+    };
+    let progress = comparator.progress_reporter().start(
+        distribution_size as u64,
+        "Computing k-sample permutation distribution".into(),
+    );
+    let mut permuted = population.clone();
+    let permutation_statistics: Vec<f64> = (0..distribution_size)
+        .map(|_| {
+            permuted.shuffle(&mut rng);
+            let statistic = group_statistics.statistic(&group_statistics.groups(&permuted));
+            progress.inc(1);
+            statistic
+        })
+        .collect();
+    progress.finish();
+
+    let pvalue_estimate =
+        PValueEstimate::from_distribution(&permutation_statistics, observed, pvalue_options);
+    let effect_size = EffectSize::from_distribution(&permutation_statistics, observed);
+
+    Ok(KSampleComparisonResult {
+        statistic: observed,
+        statistic_kind: statistic,
+        permutation_statistics,
+        pvalue: pvalue_estimate.unbiased,
+        pvalue_estimate,
+        effect_size,
+        metadata: ComparisonMetadata {
+            comparator: comparator.name(),
+            parameters: comparator.parameters(),
+            seed,
+            log_1_size: log_sizes[0],
+            log_2_size: log_sizes[1],
+            log_1_variants: log_variants[0],
+            log_2_variants: log_variants[1],
+            distribution_size,
+            resample_size: None,
+            runtime_seconds: start.elapsed().as_secs_f64(),
+            crate_version: CRATE_VERSION.to_string(),
+        },
+        pairwise_emds,
+        log_sizes,
+        log_variants,
+    })
+}
+
+/// Computes the test statistic for an assignment of (variant indices of) cases
+/// to the groups.
+struct GroupStatistics<'a> {
+    dists: &'a dyn DistanceMatrix,
+    group_sizes: &'a [usize],
+    pooled: StochasticLanguage<usize>,
+    kind: KSampleStatistic,
+}
+
+impl<'a> GroupStatistics<'a> {
+    fn new(
+        dists: &'a dyn DistanceMatrix,
+        population: &[usize],
+        group_sizes: &'a [usize],
+        kind: KSampleStatistic,
+    ) -> Self {
+        Self {
+            dists,
+            group_sizes,
+            pooled: StochasticLanguage::from_items(population.to_vec()),
+            kind,
+        }
+    }
+
+    fn groups(&self, population: &[usize]) -> Vec<StochasticLanguage<usize>> {
+        split_languages(population, self.group_sizes)
+    }
+
+    fn statistic(&self, groups: &[StochasticLanguage<usize>]) -> f64 {
+        match self.kind {
+            KSampleStatistic::SumOfPairwiseEmds => {
+                pairwise_language_emds(self.dists, groups).sum() / 2.0
+            }
+            KSampleStatistic::EmdToPooled => {
+                let population_size: usize = self.group_sizes.iter().sum();
+                groups
+                    .iter()
+                    .zip(self.group_sizes)
+                    .map(|(group, size)| {
+                        *size as f64 / population_size as f64
+                            * language_emd(self.dists, group, &self.pooled)
+                    })
+                    .sum()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comparators::permutation_test::control_flow::ControlFlowPermutationComparator;
+    use process_mining_macros::event_log;
+
+    #[test]
+    fn test_k_sample_detects_differing_log() {
+        let log_1 = event_log!([a, b], [a, b], [a, b], [a, c]; base_timestamp=EPOCH);
+        let log_2 = event_log!([a, b], [a, b], [a, c], [a, b]; base_timestamp=EPOCH);
+        let log_3 = event_log!([d, e, f], [d, e, f], [d, e, f], [d, e]; base_timestamp=EPOCH);
+
+        for statistic in [
+            KSampleStatistic::SumOfPairwiseEmds,
+            KSampleStatistic::EmdToPooled,
+        ] {
+            let result = compare_k_samples(
+                &ControlFlowPermutationComparator,
+                &[&log_1, &log_2, &log_3],
+                200,
+                statistic,
+                &PValueOptions::default(),
+                Some(42),
+            )
+            .unwrap();
+
+            assert_eq!(result.permutation_statistics.len(), 200);
+            assert!(result.pvalue_estimate.unbiased < 0.05);
+            assert_eq!(result.pairwise_emds.shape(), &[3, 3]);
+            assert_eq!(result.pairwise_emds[(0, 1)], 0.0);
+            assert_eq!(result.pairwise_emds[(0, 2)], result.pairwise_emds[(2, 0)]);
+            assert!(result.pairwise_emds[(0, 2)] > 0.5);
+            assert_eq!(result.log_variants, vec![2, 2, 2]);
+            assert_eq!(result.metadata.distribution_size, 200);
+
+            let reloaded = KSampleComparisonResult::from_json(&result.to_json().unwrap()).unwrap();
+            assert_eq!(reloaded.pairwise_emds, result.pairwise_emds);
+            assert_eq!(reloaded.statistic_kind, statistic);
+            assert_eq!(reloaded.metadata, result.metadata);
+        }
+    }
+
+    #[test]
+    fn test_k_sample_with_too_few_logs() {
+        let log = event_log!([a, b]; base_timestamp=EPOCH);
+        for logs in [vec![], vec![&log]] {
+            let result = compare_k_samples(
+                &ControlFlowPermutationComparator,
+                &logs,
+                10,
+                KSampleStatistic::SumOfPairwiseEmds,
+                &PValueOptions::default(),
+                Some(0),
+            );
+            assert!(matches!(
+                result,
+                Err(ComparisonError::TooFewLogs { found }) if found == logs.len()
+            ));
+        }
+    }
+}
@@ -1,3 +1,12 @@
+pub mod case_duration;
 pub mod control_flow;
+pub mod event_attributes;
+pub mod k_sample;
+pub mod paired;
 pub mod permutation_test_comparator;
+pub mod resource;
+pub mod service_waiting_time_levenshtein;
+pub mod stratification;
 pub mod timed_levenshtein;
+pub mod trace_attributes;
+pub mod waiting_time_levenshtein;
@@ -0,0 +1,363 @@
+//! Paired comparisons of event logs containing the same cases, e.g., re-simulated
+//! cases or A/B replays of the same orders.
+//!
+//! Traces are matched by their trace id (`concept:name`). Under the null hypothesis,
+//! the two traces of each pair are exchangeable, so the test distribution is computed
+//! by randomly swapping the traces within each pair (a sign-flip permutation test)
+//! and computing the EMD between the resulting event logs. Compared to the unpaired
+//! permutation test, the variation between cases does not mask differences between
+//! the event logs.
+//!
+//! The swaps are applied to the traces rather than to the distances between the
+//! traces of each pair (the comparator's `cost`): The cost is symmetric and
+//! non-negative, so swapping the traces of a pair does not change their distance,
+//! and a sign-flip test on the pair distances would have a degenerate distribution
+//! (every flip gives the same statistic). The pair distances are reported in the
+//! result as a descriptive measure of how much the individual cases changed.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Debug,
+    hash::Hash,
+    time::Instant,
+};
+
+use process_mining::EventLog;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    comparators::common::{
+        comparison_error::ComparisonError,
+        result::{ComparisonMetadata, ComparisonResult, CRATE_VERSION},
+        stochastic_language::StochasticLanguage,
+    },
+    pairwise::language_emd,
+    stats::{
+        effect_size::EffectSize,
+        pvalue::{PValueEstimate, PValueOptions},
+        sequential::SequentialSummary,
+    },
+    utils::{
+        attributes::{attribute_error::AttributeResult, HasAttributes},
+        constants::TRACEID_KEY,
+        nullable_float,
+    },
+};
+
+use super::permutation_test_comparator::PermutationTestComparator;
+
+/// The distance between the two traces of a pair.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PairDistance {
+    /// The trace id shared by the two traces.
+    pub case_id: String,
+    /// The distance between the representations of the two traces (the comparator's
+    /// `cost`).
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub distance: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairedComparisonResult {
+    /// The EMD measured between the matched traces of the two event logs.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub logs_emd: f64,
+    /// The EMDs computed after randomly swapping traces within pairs.
+    #[serde(deserialize_with = "nullable_float::deserialize_vec")]
+    pub permutation_emds: Vec<f64>,
+    /// The p-value `(b + 1) / (m + 1)`, where `b` of the `m` EMDs in the test
+    /// distribution exceed the observed EMD. See `pvalue_estimate` for the raw
+    /// fraction `b / m` and the confidence interval.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub pvalue: f64,
+    /// The p-value estimate including its Monte Carlo error.
+    pub pvalue_estimate: PValueEstimate,
+    /// Effect sizes of the observed EMD relative to the test distribution.
+    pub effect_size: EffectSize,
+    /// The distance between the traces of each pair, in the order of the first
+    /// event log. Descriptive only; The test statistic is `logs_emd`.
+    pub pair_distances: Vec<PairDistance>,
+    /// The mean distance between the traces of a pair.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub mean_pair_distance: f64,
+    /// The trace ids of the traces of the first event log without a partner in the
+    /// second event log. Not part of the test.
+    pub unmatched_1: Vec<String>,
+    /// The trace ids of the traces of the second event log without a partner in the
+    /// first event log. Not part of the test.
+    pub unmatched_2: Vec<String>,
+    /// Information about how the result was obtained. The log sizes are the number
+    /// of pairs.
+    pub metadata: ComparisonMetadata,
+}
+
+impl ComparisonResult for PairedComparisonResult {
+    fn logs_emd(&self) -> f64 {
+        self.logs_emd
+    }
+
+    fn distribution(&self) -> &[f64] {
+        &self.permutation_emds
+    }
+
+    fn pvalue(&self) -> f64 {
+        self.pvalue
+    }
+
+    fn pvalue_estimate(&self) -> &PValueEstimate {
+        &self.pvalue_estimate
+    }
+
+    fn effect_size(&self) -> &EffectSize {
+        &self.effect_size
+    }
+
+    fn metadata(&self) -> &ComparisonMetadata {
+        &self.metadata
+    }
+
+    fn sequential(&self) -> Option<&SequentialSummary> {
+        None
+    }
+}
+
+/// The traces of both event logs matched by trace id, as indices of the traces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceMatching {
+    /// The pairs `(index in log_1, index in log_2)` of traces with the same trace
+    /// id, in the order of the first event log.
+    pub pairs: Vec<(usize, usize)>,
+    /// The indices of the traces of the first event log without a partner.
+    pub unmatched_1: Vec<usize>,
+    /// The indices of the traces of the second event log without a partner.
+    pub unmatched_2: Vec<usize>,
+}
+
+/// Match the traces of two event logs by their trace id (`concept:name`). If a trace
+/// id occurs multiple times, the occurrences are matched in order.
+///
+/// Returns an `Err` if a trace has no trace id.
+pub fn match_traces(log_1: &EventLog, log_2: &EventLog) -> AttributeResult<TraceMatching> {
+    let mut partners: HashMap<String, VecDeque<usize>> = HashMap::new();
+    for (index, trace) in log_2.traces.iter().enumerate() {
+        partners
+            .entry(trace.get_string_by_key(TRACEID_KEY)?)
+            .or_default()
+            .push_back(index);
+    }
+
+    let mut pairs = Vec::new();
+    let mut unmatched_1 = Vec::new();
+    for (index, trace) in log_1.traces.iter().enumerate() {
+        match partners
+            .get_mut(&trace.get_string_by_key(TRACEID_KEY)?)
+            .and_then(VecDeque::pop_front)
+        {
+            Some(partner) => pairs.push((index, partner)),
+            None => unmatched_1.push(index),
+        }
+    }
+
+    let mut unmatched_2: Vec<usize> = partners.into_values().flatten().collect();
+    unmatched_2.sort();
+    Ok(TraceMatching {
+        pairs,
+        unmatched_1,
+        unmatched_2,
+    })
+}
+
+/// Run the paired permutation test. See [`PermutationTestComparator::compare_paired`].
+pub(super) fn run_paired_test<T, C>(
+    comparator: &C,
+    log_1: &EventLog,
+    log_2: &EventLog,
+    distribution_size: usize,
+    pvalue_options: &PValueOptions,
+    seed: Option<u64>,
+) -> Result<PairedComparisonResult, ComparisonError>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: PermutationTestComparator<T> + ?Sized,
+{
+    let start = Instant::now();
+    let matching = match_traces(log_1, log_2)?;
+    if matching.pairs.is_empty() {
+        return Err(ComparisonError::NoMatchedTraces);
+    }
+    let (behavior_1, behavior_2) = comparator.extract_representations(log_1, log_2)?;
+    // The pairs refer to the traces, so each trace needs its representation
+    ComparisonError::check_representation_count(log_1.traces.len(), behavior_1.len())?;
+    ComparisonError::check_representation_count(log_2.traces.len(), behavior_2.len())?;
+
+    let trace_id = |log: &EventLog, index: usize| log.traces[index].get_string_by_key(TRACEID_KEY);
+    let pair_distances = matching
+        .pairs
+        .iter()
+        .map(|(index_1, index_2)| {
+            Ok(PairDistance {
+                case_id: trace_id(log_1, *index_1)?,
+                distance: comparator.cost(&behavior_1[*index_1], &behavior_2[*index_2]),
+            })
+        })
+        .collect::<AttributeResult<Vec<_>>>()?;
+    let mean_pair_distance =
+        pair_distances.iter().map(|pair| pair.distance).sum::<f64>() / pair_distances.len() as f64;
+
+    let (matched_1, matched_2): (Vec<T>, Vec<T>) = matching
+        .pairs
+        .iter()
+        .map(|(index_1, index_2)| (behavior_1[*index_1].clone(), behavior_2[*index_2].clone()))
+        .unzip();
+    let variant_distances = comparator.compute_variant_distances(&matched_1, &matched_2);
+    let variant_index = |item: &T| variant_distances.variants.binary_search(item).unwrap();
+    let pairs: Vec<(usize, usize)> = matched_1
+        .iter()
+        .zip(matched_2.iter())
+        .map(|(item_1, item_2)| (variant_index(item_1), variant_index(item_2)))
+        .collect();
+
+    let emd = |pairs: &mut dyn Iterator<Item = (usize, usize)>| {
+        let (sample_1, sample_2): (Vec<usize>, Vec<usize>) = pairs.unzip();
+        language_emd(
+            variant_distances.distances.as_ref(),
+            &StochasticLanguage::from_items(sample_1),
+            &StochasticLanguage::from_items(sample_2),
+        )
+    };
+    let logs_emd = emd(&mut pairs.iter().copied());
+
+    let mut rng = if let Some(s) = seed {
+        StdRng::seed_from_u64(s)
+    } else {
+        StdRng::from_entropy()
+    };
+    let progress = comparator.progress_reporter().start(
+        distribution_size as u64,
+        "Computing paired permutation EMD distribution".into(),
+    );
+    let permutation_emds: Vec<f64> = (0..distribution_size)
+        .map(|_| {
+            let mut flipped = pairs.iter().map(|(variant_1, variant_2)| {
+                if rng.gen_bool(0.5) {
+                    (*variant_2, *variant_1)
+                } else {
+                    (*variant_1, *variant_2)
+                }
+            });
+            let sample_emd = emd(&mut flipped);
+            progress.inc(1);
+            sample_emd
+        })
+        .collect();
+    progress.finish();
+
+    let pvalue_estimate =
+        PValueEstimate::from_distribution(&permutation_emds, logs_emd, pvalue_options);
+    let effect_size = EffectSize::from_distribution(&permutation_emds, logs_emd);
+    let num_variants = |items: &[T]| items.iter().collect::<HashSet<_>>().len();
+
+    Ok(PairedComparisonResult {
+        logs_emd,
+        pvalue: pvalue_estimate.unbiased,
+        pvalue_estimate,
+        effect_size,
+        pair_distances,
+        mean_pair_distance,
+        unmatched_1: matching
+            .unmatched_1
+            .iter()
+            .map(|index| trace_id(log_1, *index))
+            .collect::<AttributeResult<_>>()?,
+        unmatched_2: matching
+            .unmatched_2
+            .iter()
+            .map(|index| trace_id(log_2, *index))
+            .collect::<AttributeResult<_>>()?,
+        metadata: ComparisonMetadata {
+            comparator: comparator.name(),
+            parameters: comparator.parameters(),
+            seed,
+            log_1_size: matched_1.len(),
+            log_2_size: matched_2.len(),
+            log_1_variants: num_variants(&matched_1),
+            log_2_variants: num_variants(&matched_2),
+            distribution_size: permutation_emds.len(),
+            resample_size: None,
+            runtime_seconds: start.elapsed().as_secs_f64(),
+            crate_version: CRATE_VERSION.to_string(),
+        },
+        permutation_emds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comparators::permutation_test::control_flow::ControlFlowPermutationComparator,
+        utils::attributes::add_or_overwrite_attribute,
+    };
+    use process_mining::event_log::AttributeValue;
+    use process_mining_macros::event_log;
+
+    fn set_case_ids(log: &mut EventLog, ids: &[&str]) {
+        log.traces.iter_mut().zip(ids).for_each(|(trace, id)| {
+            add_or_overwrite_attribute(trace, TRACEID_KEY, AttributeValue::String(id.to_string()))
+        });
+    }
+
+    #[test]
+    fn test_paired_comparison() {
+        let mut log_1 = event_log!([a, b], [a, c], [a, b], [a, d], [x]; base_timestamp=EPOCH);
+        let mut log_2 = event_log!([a, b], [a, b], [a, c], [a, b], [y]; base_timestamp=EPOCH);
+        set_case_ids(&mut log_1, &["1", "2", "3", "4", "5"]);
+        set_case_ids(&mut log_2, &["1", "3", "2", "4", "6"]);
+
+        let matching = match_traces(&log_1, &log_2).unwrap();
+        assert_eq!(matching.pairs, vec![(0, 0), (1, 2), (2, 1), (3, 3)]);
+
+        let result = ControlFlowPermutationComparator
+            .compare_paired(&log_1, &log_2, 100, &PValueOptions::default(), Some(1))
+            .unwrap();
+        assert_eq!(result.unmatched_1, vec!["5".to_string()]);
+        assert_eq!(result.unmatched_2, vec!["6".to_string()]);
+        // Only case 4 differs: <a,d> vs. <a,b>
+        assert_eq!(
+            result
+                .pair_distances
+                .iter()
+                .map(|pair| pair.distance)
+                .collect::<Vec<_>>(),
+            vec![0.0, 0.0, 0.0, 0.5]
+        );
+        assert_eq!(result.mean_pair_distance, 0.125);
+        assert_eq!(result.metadata.log_1_size, 4);
+        assert_eq!(result.permutation_emds.len(), 100);
+        assert!(result.logs_emd > 0.0);
+        assert!(result
+            .permutation_emds
+            .iter()
+            .all(|emd| *emd <= result.logs_emd + 1e-9));
+    }
+
+    #[test]
+    fn test_paired_comparison_without_pairs() {
+        let mut log_1 = event_log!([a, b]; base_timestamp=EPOCH);
+        let mut log_2 = event_log!([a, b]; base_timestamp=EPOCH);
+        set_case_ids(&mut log_1, &["1"]);
+        set_case_ids(&mut log_2, &["2"]);
+
+        assert!(matches!(
+            ControlFlowPermutationComparator.compare_paired(
+                &log_1,
+                &log_2,
+                10,
+                &PValueOptions::default(),
+                Some(1)
+            ),
+            Err(ComparisonError::NoMatchedTraces)
+        ));
+    }
+}
@@ -1,33 +1,106 @@
-use std::{collections::HashSet, fmt::Debug, hash::Hash};
+use std::{collections::HashSet, fmt::Debug, hash::Hash, time::Instant};
 
 use itertools::Itertools;
 use ndarray::Array2;
 use process_mining::EventLog;
 use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    comparators::common::stochastic_language::StochasticLanguage,
+    comparators::common::{
+        comparison_error::ComparisonError,
+        result::{
+            short_type_name, ComparatorParameters, ComparisonMetadata, ComparisonResult,
+            CRATE_VERSION,
+        },
+        stochastic_language::StochasticLanguage,
+    },
+    distance::matrix::DistanceMatrix,
     emd::compute_emd,
-    utils::{attributes::attribute_error::AttributeResult, progress::build_progress_bar},
+    stats::{
+        effect_size::EffectSize,
+        pvalue::{PValueEstimate, PValueOptions},
+        sequential::{sequential_distribution, SamplingMode, SequentialOptions, SequentialSummary},
+    },
+    utils::{
+        attributes::attribute_error::AttributeResult,
+        nullable_float,
+        progress::{IndicatifReporter, ProgressReporter},
+    },
 };
 
-#[derive(Debug)]
+use super::{
+    paired::{run_paired_test, PairedComparisonResult},
+    stratification::{Strata, Stratification},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PermutationTestComparisonResult {
     /// The EMD measured between the two original event logs.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
     pub logs_emd: f64,
     /// The EMDs computed in the permutation phase.
+    #[serde(deserialize_with = "nullable_float::deserialize_vec")]
     pub permutation_emds: Vec<f64>,
-    /// The computed p-value
+    /// The p-value `(b + 1) / (m + 1)`, where `b` of the `m` EMDs in the test
+    /// distribution exceed the observed EMD. See `pvalue_estimate` for the raw
+    /// fraction `b / m` and the confidence interval.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
     pub pvalue: f64,
+    /// The p-value estimate including its Monte Carlo error.
+    #[serde(default)]
+    pub pvalue_estimate: PValueEstimate,
+    /// Effect sizes of the observed EMD relative to the test distribution. See
+    /// [`EffectSize`] for their interpretation.
+    #[serde(default)]
+    pub effect_size: EffectSize,
+    /// Information about how the result was obtained.
+    pub metadata: ComparisonMetadata,
+    /// The summary of the sequential test, if the comparison was run with
+    /// [`compare_sequential`](PermutationTestComparator::compare_sequential).
+    #[serde(default)]
+    pub sequential: Option<SequentialSummary>,
+}
+
+impl ComparisonResult for PermutationTestComparisonResult {
+    fn logs_emd(&self) -> f64 {
+        self.logs_emd
+    }
+
+    fn distribution(&self) -> &[f64] {
+        &self.permutation_emds
+    }
+
+    fn pvalue(&self) -> f64 {
+        self.pvalue
+    }
+
+    fn metadata(&self) -> &ComparisonMetadata {
+        &self.metadata
+    }
+
+    fn pvalue_estimate(&self) -> &PValueEstimate {
+        &self.pvalue_estimate
+    }
+
+    fn effect_size(&self) -> &EffectSize {
+        &self.effect_size
+    }
+
+    fn sequential(&self) -> Option<&SequentialSummary> {
+        self.sequential.as_ref()
+    }
 }
 
 /// Process Hypothesis Testing based on the Permutation Test and EMD. Proposed in
 /// "Hypothesis Testing for Processes" by Pitsch et al ([DOI](https://doi.org/10.1109/ICPM66919.2025.11220677)).
 ///
 /// A permutation distribution is computed by repeatedly shuffling cases between
-/// the two event logs and computing the EMD. The p-value is computed as the fraction
-/// of permutation distribution values that are greater than the EMD between the
-/// two event logs.
+/// the two event logs and computing the EMD. The reported p-value is the estimate
+/// `(b + 1) / (m + 1)`, where `b` of the `m` permutation EMDs exceed the EMD between
+/// the two event logs. Whether equal EMDs count as exceeding it is configured by the
+/// [`PValueOptions`]. See [`PValueEstimate`] for the raw fraction `b / m` and its
+/// Clopper-Pearson confidence interval.
 pub trait PermutationTestComparator<T>
 where
     T: Hash + Eq + Clone + Ord + Debug,
@@ -42,6 +115,26 @@ where
         log_2: &EventLog,
     ) -> AttributeResult<(Vec<T>, Vec<T>)>;
 
+    /// The name of the comparator recorded in the [`ComparisonMetadata`]. Defaults
+    /// to the unqualified type name.
+    fn name(&self) -> String {
+        short_type_name(std::any::type_name::<Self>())
+    }
+
+    /// The parameters of the comparator recorded in the [`ComparisonMetadata`].
+    fn parameters(&self) -> ComparatorParameters {
+        ComparatorParameters::new()
+    }
+
+    /// Where the progress of long-running computations (distance matrices, test
+    /// distributions) is reported. Defaults to progress bars on stderr; See
+    /// [`WithProgress`] to silence or redirect it.
+    ///
+    /// [`WithProgress`]: crate::comparators::common::with_progress::WithProgress
+    fn progress_reporter(&self) -> &dyn ProgressReporter {
+        &IndicatifReporter
+    }
+
     /// Compare two event logs.
     ///
     /// - Returns an `Err` if required attributes are not present on the events.
@@ -59,57 +152,180 @@ where
         distribution_size: usize,
         seed: Option<u64>,
     ) -> AttributeResult<PermutationTestComparisonResult> {
-        let (behavior_1, behavior_2) = self.extract_representations(log_1, log_2)?;
-
-        // TODO: Why dont I sort and then dedup?
-        let mut combined_variants: Vec<T> = behavior_1 // Use a Vec so the order is fixed
-            .iter()
-            .chain(behavior_2.iter())
-            .cloned()
-            .collect::<HashSet<T>>()
-            .into_iter()
-            .collect();
-        combined_variants.sort();
-        let stoch_lang_1 = StochasticLanguage::from_items(behavior_1.clone());
-        let stoch_lang_2 = StochasticLanguage::from_items(behavior_2.clone());
-
-        let large_distance_matrix = self.compute_symmetric_distance_matrix(&combined_variants);
+        self.compare_with_options(
+            log_1,
+            log_2,
+            distribution_size,
+            &PValueOptions::default(),
+            seed,
+        )
+    }
 
-        let log_1_log_2_distances = project_distance_matrix(
-            &large_distance_matrix,
-            &combined_variants,
-            &stoch_lang_1,
-            &stoch_lang_2,
-        );
+    /// Compare two event logs like [`compare`](PermutationTestComparator::compare),
+    /// with options for computing the p-value, e.g., whether EMDs equal to the observed
+    /// EMD count as exceeding it.
+    fn compare_with_options(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+        distribution_size: usize,
+        pvalue_options: &PValueOptions,
+        seed: Option<u64>,
+    ) -> AttributeResult<PermutationTestComparisonResult> {
+        run_permutation_test(
+            self,
+            log_1,
+            log_2,
+            distribution_size,
+            SamplingMode::Fixed(pvalue_options),
+            seed,
+        )
+    }
 
-        let logs_emd = compute_emd(
-            stoch_lang_1.frequencies,
-            stoch_lang_2.frequencies,
-            &log_1_log_2_distances,
+    /// Compare two event logs using a sequential test: Permutations are computed
+    /// until the p-value is confidently above or below the significance level (see
+    /// [`SequentialOptions`]), or `max_distribution_size` permutations have been
+    /// computed. The p-value is computed from the permutations actually used, which
+    /// are reported in [`PermutationTestComparisonResult::sequential`].
+    ///
+    /// Returns an `Err` under the same conditions as [`compare`](PermutationTestComparator::compare).
+    fn compare_sequential(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+        max_distribution_size: usize,
+        options: &SequentialOptions,
+        seed: Option<u64>,
+    ) -> AttributeResult<PermutationTestComparisonResult> {
+        run_permutation_test(
+            self,
+            log_1,
+            log_2,
+            max_distribution_size,
+            SamplingMode::Sequential(options),
+            seed,
         )
-        .emd;
+    }
 
-        let permutation_emds = compute_permutation_test_distribution(
-            &large_distance_matrix,
-            combined_variants,
+    /// Compare two event logs using a stratified permutation test: Cases are only
+    /// shuffled between the event logs within the strata defined by `stratification`,
+    /// e.g., by a trace attribute. This controls for a confounding variable whose
+    /// distribution differs between the event logs.
+    ///
+    /// The stratification is recorded in the parameters of the [`ComparisonMetadata`].
+    ///
+    /// Returns an `Err` under the same conditions as [`compare`](PermutationTestComparator::compare),
+    /// if the stratification fails (see [`Stratification::strata`]), or if the
+    /// comparator does not extract one representation per trace.
+    fn compare_stratified(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+        distribution_size: usize,
+        stratification: &Stratification,
+        pvalue_options: &PValueOptions,
+        seed: Option<u64>,
+    ) -> Result<PermutationTestComparisonResult, ComparisonError> {
+        let start = Instant::now();
+        let strata = stratification.strata(log_1, log_2)?;
+        let (behavior_1, behavior_2) = self.extract_representations(log_1, log_2)?;
+        // The strata are assigned to the traces, so each trace needs its representation
+        ComparisonError::check_representation_count(log_1.traces.len(), behavior_1.len())?;
+        ComparisonError::check_representation_count(log_2.traces.len(), behavior_2.len())?;
+
+        let variant_distances = self.compute_variant_distances(&behavior_1, &behavior_2);
+        let mut result = compare_with_variant_distances(
+            self,
+            &variant_distances,
             behavior_1,
             behavior_2,
+            Some(&strata),
             distribution_size,
+            SamplingMode::Fixed(pvalue_options),
             seed,
         );
+        result.metadata.runtime_seconds = start.elapsed().as_secs_f64();
+        result.metadata.parameters.insert(
+            "stratification".to_string(),
+            serde_json::to_value(stratification).unwrap_or_default(),
+        );
+        Ok(result)
+    }
+
+    /// Compare two event logs containing the same cases (matched by trace id, see
+    /// [`match_traces`]) using a paired permutation test: The test distribution is
+    /// computed by randomly swapping the traces within each pair. Traces without a
+    /// partner are excluded from the test, and reported in the result.
+    ///
+    /// See the [`paired`](super::paired) module for why the traces, not their
+    /// distances, are swapped.
+    ///
+    /// Returns an `Err` under the same conditions as [`compare`](PermutationTestComparator::compare),
+    /// if a trace has no trace id (`concept:name`), if no traces can be matched, or if
+    /// the comparator does not extract one representation per trace.
+    ///
+    /// [`match_traces`]: super::paired::match_traces
+    fn compare_paired(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+        distribution_size: usize,
+        pvalue_options: &PValueOptions,
+        seed: Option<u64>,
+    ) -> Result<PairedComparisonResult, ComparisonError> {
+        run_paired_test(self, log_1, log_2, distribution_size, pvalue_options, seed)
+    }
 
-        let pvalue = permutation_emds
+    /// Compute the distances between all pairs of distinct representations in
+    /// `behavior_1` and `behavior_2` (see [`variant_distance_matrix`]).
+    ///
+    /// [`variant_distance_matrix`]: PermutationTestComparator::variant_distance_matrix
+    fn compute_variant_distances<'a>(
+        &'a self,
+        behavior_1: &[T],
+        behavior_2: &[T],
+    ) -> VariantDistances<'a, T>
+    where
+        T: 'a,
+    {
+        // TODO: Why dont I sort and then dedup?
+        let mut variants: Vec<T> = behavior_1 // Use a Vec so the order is fixed
             .iter()
-            .filter(|emd| **emd > logs_emd)
-            .collect_vec()
-            .len() as f64
-            / distribution_size as f64;
-
-        Ok(PermutationTestComparisonResult {
-            logs_emd,
-            pvalue,
-            permutation_emds,
-        })
+            .chain(behavior_2.iter())
+            .cloned()
+            .collect::<HashSet<T>>()
+            .into_iter()
+            .collect();
+        variants.sort();
+
+        let distances = self.variant_distance_matrix(&variants, self.progress_reporter());
+        VariantDistances {
+            variants,
+            distances,
+        }
+    }
+
+    /// The distance matrix between each pair of `variants` used for the tests.
+    /// Defaults to the dense matrix computed by [`compute_symmetric_distance_matrix`].
+    /// For very large sets of variants, see [`LazyComparator`] to only compute the
+    /// distances that are actually needed.
+    ///
+    /// The progress is reported to `reporter`, i.e., the [`progress_reporter`] of the
+    /// outermost comparator, so that wrapping comparators do not change where it is
+    /// reported.
+    ///
+    /// [`compute_symmetric_distance_matrix`]: PermutationTestComparator::compute_symmetric_distance_matrix
+    /// [`LazyComparator`]: crate::comparators::common::lazy_comparator::LazyComparator
+    /// [`progress_reporter`]: PermutationTestComparator::progress_reporter
+    fn variant_distance_matrix<'a>(
+        &'a self,
+        variants: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Box<dyn DistanceMatrix + 'a>
+    where
+        T: 'a,
+    {
+        Box::new(self.compute_symmetric_distance_matrix(variants, reporter))
     }
 
     /// Compute the distance matrix between each pair of variants using the [`cost`]
@@ -117,12 +333,16 @@ where
     ///
     /// The output matrix has the dimensions `(variants.len(), variants.len())`.
     /// To compute the matrix, it is assumed that the [`cost`] function is symmetric,
-    /// i.e., `cost(a,b)=cost(b,a)`.
+    /// i.e., `cost(a,b)=cost(b,a)`. The progress is reported to `reporter`.
     ///
     /// [`cost`]: PermutationTestComparator::cost
-    fn compute_symmetric_distance_matrix(&self, variants: &[T]) -> Array2<f64> {
+    fn compute_symmetric_distance_matrix(
+        &self,
+        variants: &[T],
+        reporter: &dyn ProgressReporter,
+    ) -> Array2<f64> {
         let mut mat = Array2::zeros((variants.len(), variants.len()));
-        let progress = build_progress_bar(
+        let progress = reporter.start(
             variants.len().pow(2) as u64,
             format!(
                 "Computing complete distance matrix ({}x{})",
@@ -150,13 +370,165 @@ where
     }
 }
 
+/// Run the permutation test, either with a fixed distribution size or sequentially.
+fn run_permutation_test<T, C>(
+    comparator: &C,
+    log_1: &EventLog,
+    log_2: &EventLog,
+    distribution_size: usize,
+    mode: SamplingMode,
+    seed: Option<u64>,
+) -> AttributeResult<PermutationTestComparisonResult>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: PermutationTestComparator<T> + ?Sized,
+{
+    let start = Instant::now();
+    let (behavior_1, behavior_2) = comparator.extract_representations(log_1, log_2)?;
+    let variant_distances = comparator.compute_variant_distances(&behavior_1, &behavior_2);
+
+    let mut result = compare_with_variant_distances(
+        comparator,
+        &variant_distances,
+        behavior_1,
+        behavior_2,
+        None,
+        distribution_size,
+        mode,
+        seed,
+    );
+    result.metadata.runtime_seconds = start.elapsed().as_secs_f64();
+    Ok(result)
+}
+
+/// The distances between all pairs of distinct (and sorted) representations, e.g.,
+/// of two event logs. Can be reused to compare different subsets of these representations,
+/// see [`compare_with_variant_distances`].
+#[derive(Debug)]
+pub struct VariantDistances<'a, T> {
+    /// The distinct representations, sorted. Maps each row/column of `distances`
+    /// to a representation.
+    pub variants: Vec<T>,
+    /// The symmetric distance matrix between the `variants`.
+    pub distances: Box<dyn DistanceMatrix + 'a>,
+}
+
+/// Run the permutation test between `behavior_1` and `behavior_2` using precomputed
+/// distances between their representations. The `variant_distances` must contain
+/// all representations of `behavior_1` and `behavior_2`. If `strata` are given,
+/// cases are only shuffled within their stratum.
+///
+/// The runtime recorded in the metadata does not include the computation of the
+/// distances.
+#[allow(clippy::too_many_arguments)]
+pub fn compare_with_variant_distances<T, C>(
+    comparator: &C,
+    variant_distances: &VariantDistances<'_, T>,
+    behavior_1: Vec<T>,
+    behavior_2: Vec<T>,
+    strata: Option<&Strata>,
+    distribution_size: usize,
+    mode: SamplingMode,
+    seed: Option<u64>,
+) -> PermutationTestComparisonResult
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: PermutationTestComparator<T> + ?Sized,
+{
+    let start = Instant::now();
+    let combined_variants = &variant_distances.variants;
+    let large_distance_matrix = variant_distances.distances.as_ref();
+
+    let stoch_lang_1 = StochasticLanguage::from_items(behavior_1.clone());
+    let stoch_lang_2 = StochasticLanguage::from_items(behavior_2.clone());
+
+    let log_1_log_2_distances = project_distance_matrix(
+        large_distance_matrix,
+        combined_variants,
+        &stoch_lang_1,
+        &stoch_lang_2,
+    );
+
+    let (log_1_size, log_2_size) = (behavior_1.len(), behavior_2.len());
+    let (log_1_variants, log_2_variants) =
+        (stoch_lang_1.variants.len(), stoch_lang_2.variants.len());
+
+    let logs_emd = compute_emd(
+        stoch_lang_1.frequencies,
+        stoch_lang_2.frequencies,
+        &log_1_log_2_distances,
+    )
+    .emd;
+
+    let (permutation_emds, sequential) = match mode {
+        SamplingMode::Fixed(_) => (
+            compute_permutation_test_distribution(
+                large_distance_matrix,
+                combined_variants.clone(),
+                behavior_1,
+                behavior_2,
+                strata,
+                distribution_size,
+                seed,
+                comparator.progress_reporter(),
+            ),
+            None,
+        ),
+        SamplingMode::Sequential(options) => {
+            let progress = comparator.progress_reporter().start(
+                distribution_size as u64,
+                "Computing permutation EMD distribution (sequential)".into(),
+            );
+            let sampler = PermutationSampler::new(
+                large_distance_matrix,
+                combined_variants,
+                &behavior_1,
+                &behavior_2,
+                seed,
+            )
+            .with_optional_strata(strata)
+            .inspect(|_| progress.inc(1));
+            let (emds, summary) =
+                sequential_distribution(sampler, logs_emd, distribution_size, options);
+            progress.finish();
+            (emds, Some(summary))
+        }
+    };
+
+    let pvalue_estimate =
+        PValueEstimate::from_distribution(&permutation_emds, logs_emd, mode.pvalue_options());
+    let effect_size = EffectSize::from_distribution(&permutation_emds, logs_emd);
+
+    PermutationTestComparisonResult {
+        logs_emd,
+        pvalue: pvalue_estimate.unbiased,
+        pvalue_estimate,
+        effect_size,
+        metadata: ComparisonMetadata {
+            comparator: comparator.name(),
+            parameters: comparator.parameters(),
+            seed,
+            log_1_size,
+            log_2_size,
+            log_1_variants,
+            log_2_variants,
+            distribution_size: permutation_emds.len(),
+            resample_size: None,
+            runtime_seconds: start.elapsed().as_secs_f64(),
+            crate_version: CRATE_VERSION.to_string(),
+        },
+        permutation_emds,
+        sequential,
+    }
+}
+
 /// Project the distance matrix to to contain only the rows for items in `population_1`
 /// and the columns for items in `population_2`.
 ///
 /// `dist_matrix_source_population` is the population for which the distance matrix
 /// was created, i.e., an "index" mapping rows/columns to items.
-pub fn project_distance_matrix<T: Clone + Eq + Hash>(
-    dists: &Array2<f64>,
+pub fn project_distance_matrix<T: Clone + Eq + Hash, D: DistanceMatrix + ?Sized>(
+    dists: &D,
     dist_matrix_source_population: &[T],
     population_1: &StochasticLanguage<T>,
     population_2: &StochasticLanguage<T>,
@@ -182,9 +554,7 @@ pub fn project_distance_matrix<T: Clone + Eq + Hash>(
         })
         .collect();
 
-    dists
-        .select(ndarray::Axis(0), &pop_1_indices)
-        .select(ndarray::Axis(1), &pop_2_indices)
+    dists.select(&pop_1_indices, &pop_2_indices)
 }
 
 /// Compute the permutation distribution between `behavior_1` and `behavior_2`.
@@ -192,73 +562,194 @@ pub fn project_distance_matrix<T: Clone + Eq + Hash>(
 /// * `dists`: The distance matrix computed between `behavior_1` and `behavior_2`
 /// * `distance_matrix_source_population`: The population used to compute the distance
 ///   matrix. Used to map representations to their row/column in the distance matrix.
+/// * `strata`: If given, the stratified mode: Cases are only shuffled between
+///   `behavior_1` and `behavior_2` within their stratum, see [`Strata`].
 /// * `distribution_size`: The number of permutations to perform, i.e., the number
 ///   of EMDs to compute.
 /// * `seed`: The (optional) seed to use for the random shuffling.
+/// * `progress_reporter`: Where to report the progress, e.g., the comparator's
+///   [`progress_reporter`](PermutationTestComparator::progress_reporter).
+#[allow(clippy::too_many_arguments)]
 pub fn compute_permutation_test_distribution<T: PartialEq>(
-    dists: &Array2<f64>,
+    dists: &dyn DistanceMatrix,
     distance_matrix_source_population: Vec<T>,
     behavior_1: Vec<T>,
     behavior_2: Vec<T>,
+    strata: Option<&Strata>,
     distribution_size: usize,
     seed: Option<u64>,
+    progress_reporter: &dyn ProgressReporter,
 ) -> Vec<f64> {
-    let population_indices_to_variant_indices: Vec<usize> = behavior_1
-        .iter()
-        .chain(behavior_2.iter())
-        .map(|item| {
-            distance_matrix_source_population
-                .iter()
-                .position(|x| x == item)
-                .unwrap()
-        })
-        .collect();
-    let sample_size = behavior_1.len() + behavior_2.len();
-
-    let mut rng = if let Some(s) = seed {
-        StdRng::seed_from_u64(s)
-    } else {
-        StdRng::from_entropy()
-    };
-
-    let progress = build_progress_bar(
+    let progress = progress_reporter.start(
         distribution_size as u64,
         "Computing permutation EMD distribution".into(),
     );
-    let res = (0..distribution_size)
-        .map(|_| {
-            let mut sample = (0..sample_size).collect_vec();
-            sample.partial_shuffle(&mut rng, behavior_1.len());
-            let (sample_1, sample_2) = sample.split_at(behavior_1.len());
-            let translated_sample_1: StochasticLanguage<usize> = sample_1
-                .iter()
-                .map(|index| population_indices_to_variant_indices[*index])
-                .counts()
-                .into_iter()
-                .map(|(k, v)| (k, v as f64 / behavior_1.len() as f64))
-                .collect();
-            let translated_sample_2: StochasticLanguage<usize> = sample_2
-                .iter()
-                .map(|index| population_indices_to_variant_indices[*index])
-                .counts()
-                .into_iter()
-                .map(|(k, v)| (k, v as f64 / behavior_1.len() as f64))
-                .collect();
+    let res = PermutationSampler::new(
+        dists,
+        &distance_matrix_source_population,
+        &behavior_1,
+        &behavior_2,
+        seed,
+    )
+    .with_optional_strata(strata)
+    .take(distribution_size)
+    .inspect(|_| progress.inc(1))
+    .collect();
+    progress.finish();
+    res
+}
+
+/// An endless iterator over EMDs of random permutations of `behavior_1` and `behavior_2`,
+/// i.e., samples of the permutation distribution. See [`compute_permutation_test_distribution`]
+/// for the arguments.
+#[derive(Debug)]
+pub struct PermutationSampler<'a> {
+    dists: &'a dyn DistanceMatrix,
+    population_indices_to_variant_indices: Vec<usize>,
+    sample_1_size: usize,
+    /// The population indices in each stratum, and how many of them are drawn for
+    /// the first sample. Without stratification, a single stratum.
+    strata: Vec<(Vec<usize>, usize)>,
+    rng: StdRng,
+}
+
+impl<'a> PermutationSampler<'a> {
+    pub fn new<T: PartialEq>(
+        dists: &'a dyn DistanceMatrix,
+        distance_matrix_source_population: &[T],
+        behavior_1: &[T],
+        behavior_2: &[T],
+        seed: Option<u64>,
+    ) -> Self {
+        let population_indices_to_variant_indices: Vec<usize> = behavior_1
+            .iter()
+            .chain(behavior_2.iter())
+            .map(|item| {
+                distance_matrix_source_population
+                    .iter()
+                    .position(|x| x == item)
+                    .unwrap()
+            })
+            .collect();
+
+        let rng = if let Some(s) = seed {
+            StdRng::seed_from_u64(s)
+        } else {
+            StdRng::from_entropy()
+        };
+
+        Self {
+            dists,
+            strata: vec![(
+                (0..population_indices_to_variant_indices.len()).collect(),
+                behavior_1.len(),
+            )],
+            population_indices_to_variant_indices,
+            sample_1_size: behavior_1.len(),
+            rng,
+        }
+    }
+
+    /// Only shuffle cases between the samples within their stratum. The `strata`
+    /// must contain a stratum for each case of `behavior_1` and `behavior_2`.
+    pub fn with_strata(mut self, strata: &Strata) -> Self {
+        assert_eq!(
+            strata.behavior_1.len() + strata.behavior_2.len(),
+            self.population_indices_to_variant_indices.len(),
+            "Expected a stratum for each case"
+        );
+        self.strata = vec![(Vec::new(), 0); strata.labels.len()];
+        strata
+            .behavior_1
+            .iter()
+            .chain(strata.behavior_2.iter())
+            .enumerate()
+            .for_each(|(population_index, stratum)| {
+                self.strata[*stratum].0.push(population_index);
+            });
+        strata.behavior_1.iter().for_each(|stratum| {
+            self.strata[*stratum].1 += 1;
+        });
+        self
+    }
+
+    fn with_optional_strata(self, strata: Option<&Strata>) -> Self {
+        match strata {
+            Some(strata) => self.with_strata(strata),
+            None => self,
+        }
+    }
+}
+
+impl Iterator for PermutationSampler<'_> {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<f64> {
+        let mut sample_1 = Vec::with_capacity(self.sample_1_size);
+        let mut sample_2 = Vec::new();
+        self.strata
+            .iter()
+            .for_each(|(population_indices, stratum_sample_1_size)| {
+                let mut stratum = population_indices.clone();
+                stratum.partial_shuffle(&mut self.rng, *stratum_sample_1_size);
+                let (stratum_sample_1, stratum_sample_2) = stratum.split_at(*stratum_sample_1_size);
+                sample_1.extend_from_slice(stratum_sample_1);
+                sample_2.extend_from_slice(stratum_sample_2);
+            });
+        let translated_sample_1: StochasticLanguage<usize> = sample_1
+            .iter()
+            .map(|index| self.population_indices_to_variant_indices[*index])
+            .counts()
+            .into_iter()
+            .map(|(k, v)| (k, v as f64 / self.sample_1_size as f64))
+            .collect();
+        let translated_sample_2: StochasticLanguage<usize> = sample_2
+            .iter()
+            .map(|index| self.population_indices_to_variant_indices[*index])
+            .counts()
+            .into_iter()
+            .map(|(k, v)| (k, v as f64 / sample_2.len() as f64))
+            .collect();
 
-            let projected_dists = dists
-                .select(ndarray::Axis(0), &translated_sample_1.variants)
-                .select(ndarray::Axis(1), &translated_sample_2.variants);
+        let projected_dists = self
+            .dists
+            .select(&translated_sample_1.variants, &translated_sample_2.variants);
 
-            let res = compute_emd(
+        Some(
+            compute_emd(
                 translated_sample_1.frequencies,
                 translated_sample_2.frequencies,
                 &projected_dists,
             )
-            .emd;
-            progress.inc(1);
-            res
-        })
-        .collect();
-    progress.finish();
-    res
+            .emd,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comparators::permutation_test::control_flow::ControlFlowPermutationComparator;
+    use process_mining_macros::event_log;
+
+    #[test]
+    fn test_logs_of_unequal_size() {
+        let log_1 = event_log!([a]; base_timestamp=EPOCH);
+        let log_2 = event_log!([a], [a], [b]; base_timestamp=EPOCH);
+        let result = ControlFlowPermutationComparator
+            .compare(&log_1, &log_2, 50, Some(0))
+            .unwrap();
+
+        // The first sample is either [a], leaving [a] (2/3) and [b] (1/3) for the
+        // second sample, or [b], leaving only [a]
+        assert!((result.logs_emd - 1.0 / 3.0).abs() < 1e-9);
+        assert!(result
+            .permutation_emds
+            .iter()
+            .all(|emd| (emd - 1.0 / 3.0).abs() < 1e-9 || (emd - 1.0).abs() < 1e-9));
+        assert!(result
+            .permutation_emds
+            .iter()
+            .any(|emd| (emd - 1.0).abs() < 1e-9));
+    }
 }
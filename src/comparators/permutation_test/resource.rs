@@ -0,0 +1,87 @@
+use process_mining::EventLog;
+use serde::Serialize;
+
+use crate::{
+    comparators::common::{
+        extraction::{
+            project_traces_on_activity_resource, project_traces_on_resource, ResourcePerspective,
+        },
+        result::{config_parameters, ComparatorParameters},
+    },
+    distance::weighted_levenshtein::postnormalized_weighted_levenshtein_distance,
+    utils::attributes::attribute_error::AttributeResult,
+};
+
+use super::permutation_test_comparator::PermutationTestComparator;
+
+/// An implementation of the [`PermutationTestComparator`] for comparing the organizational
+/// perspective, using the postnormalized Levenshtein distance between the sequences
+/// of resources (or roles, or groups, see [`ResourcePerspective`]) executing the
+/// events of a case.
+#[derive(Default, Debug, Serialize)]
+pub struct ResourcePermutationComparator {
+    perspective: ResourcePerspective,
+}
+
+impl ResourcePermutationComparator {
+    pub fn new(perspective: ResourcePerspective) -> Self {
+        Self { perspective }
+    }
+}
+
+impl PermutationTestComparator<Vec<String>> for ResourcePermutationComparator {
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<Vec<String>>, Vec<Vec<String>>)> {
+        Ok((
+            project_traces_on_resource(log_1, self.perspective)?,
+            project_traces_on_resource(log_2, self.perspective)?,
+        ))
+    }
+
+    fn cost(&self, rep_1: &Vec<String>, rep_2: &Vec<String>) -> f64 {
+        postnormalized_weighted_levenshtein_distance(rep_1, rep_2)
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        config_parameters(self)
+    }
+}
+
+/// An implementation of the [`PermutationTestComparator`] for resource-aware control
+/// flow comparisons, using the postnormalized weighted Levenshtein distance between
+/// sequences of (activity, resource) pairs. The resource is given by the chosen
+/// [`ResourcePerspective`].
+#[derive(Default, Debug, Serialize)]
+pub struct ActivityResourcePermutationComparator {
+    perspective: ResourcePerspective,
+}
+
+impl ActivityResourcePermutationComparator {
+    pub fn new(perspective: ResourcePerspective) -> Self {
+        Self { perspective }
+    }
+}
+
+impl PermutationTestComparator<Vec<(String, String)>> for ActivityResourcePermutationComparator {
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<Vec<(String, String)>>, Vec<Vec<(String, String)>>)> {
+        Ok((
+            project_traces_on_activity_resource(log_1, self.perspective)?,
+            project_traces_on_activity_resource(log_2, self.perspective)?,
+        ))
+    }
+
+    fn cost(&self, rep_1: &Vec<(String, String)>, rep_2: &Vec<(String, String)>) -> f64 {
+        postnormalized_weighted_levenshtein_distance(rep_1, rep_2)
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        config_parameters(self)
+    }
+}
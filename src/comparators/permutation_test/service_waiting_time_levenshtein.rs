@@ -0,0 +1,68 @@
+use process_mining::EventLog;
+use serde::Serialize;
+
+use crate::{
+    binning::kmeans_binner::{KMeansArgs, KMeansBinner},
+    comparators::common::{
+        extraction::extract_service_waiting_time_representations,
+        result::{config_parameters, ComparatorParameters},
+    },
+    distance::weighted_levenshtein::{
+        postnormalized_weighted_levenshtein_distance_with, ServiceWaitingTimeCosts,
+        ServiceWaitingTimeWeights,
+    },
+    representation::ServiceWaitingTimeTrace,
+    utils::attributes::attribute_error::AttributeResult,
+};
+
+use super::permutation_test_comparator::PermutationTestComparator;
+
+/// An implementation of the [`PermutationTestComparator`] for timed control flow
+/// comparisons considering both service times and waiting times. Uses K-Means++
+/// clustering to bin service and waiting times, and the postnormalized weighted
+/// Levenshtein distance between [`ServiceWaitingTimeTrace`]s, weighing
+/// activity, service time and waiting time according to the configured
+/// [`ServiceWaitingTimeWeights`].
+#[derive(Default, Debug, Serialize)]
+pub struct ServiceWaitingTimeLevenshteinPermutationComparator {
+    binner_args: KMeansArgs,
+    weights: ServiceWaitingTimeWeights,
+}
+
+impl ServiceWaitingTimeLevenshteinPermutationComparator {
+    pub fn new(binner_args: KMeansArgs, weights: ServiceWaitingTimeWeights) -> Self {
+        Self {
+            binner_args,
+            weights,
+        }
+    }
+
+    /// The edit costs, scaling bin differences by the largest possible bin index.
+    fn costs(&self) -> ServiceWaitingTimeCosts {
+        ServiceWaitingTimeCosts::new(self.weights, self.binner_args.num_bins().saturating_sub(1))
+    }
+}
+
+impl PermutationTestComparator<ServiceWaitingTimeTrace>
+    for ServiceWaitingTimeLevenshteinPermutationComparator
+{
+    fn extract_representations(
+        &self,
+        log_1: &EventLog,
+        log_2: &EventLog,
+    ) -> AttributeResult<(Vec<ServiceWaitingTimeTrace>, Vec<ServiceWaitingTimeTrace>)> {
+        extract_service_waiting_time_representations::<KMeansBinner>(
+            log_1,
+            log_2,
+            &self.binner_args,
+        )
+    }
+
+    fn cost(&self, rep_1: &ServiceWaitingTimeTrace, rep_2: &ServiceWaitingTimeTrace) -> f64 {
+        postnormalized_weighted_levenshtein_distance_with(rep_1, rep_2, &self.costs())
+    }
+
+    fn parameters(&self) -> ComparatorParameters {
+        config_parameters(self)
+    }
+}
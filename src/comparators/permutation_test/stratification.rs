@@ -0,0 +1,220 @@
+//! Stratified permutation tests: Only shuffle cases between the event logs within
+//! strata, e.g., of "express" and "standard" orders. This controls for a confounding
+//! variable whose distribution differs between the event logs (e.g., more express
+//! orders in the second event log), so that only behavioral changes _within_ the
+//! strata are detected.
+
+use std::{collections::HashMap, fmt::Debug};
+
+use process_mining::{event_log::Trace, EventLog};
+use serde::{Serialize, Serializer};
+
+use crate::utils::attributes::{
+    attribute_error::{AttributeError, AttributeErrorKind, AttributeLevel, AttributeResult},
+    coercion::coerce_to_string,
+    HasAttributes,
+};
+
+/// How the cases are assigned to strata.
+pub enum Stratification<'a> {
+    /// Cases with the same value of a trace attribute form a stratum. Cases without
+    /// the attribute cause an error.
+    TraceAttribute(String),
+    /// Cases for which the closure returns the same key form a stratum.
+    Custom(Box<dyn Fn(&Trace) -> String + 'a>),
+}
+
+impl Debug for Stratification<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TraceAttribute(key) => f.debug_tuple("TraceAttribute").field(key).finish(),
+            Self::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+impl Serialize for Stratification<'_> {
+    /// A [`Stratification::Custom`] is serialized as `"Custom"`, as the closure
+    /// cannot be.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::TraceAttribute(key) => {
+                serializer.serialize_newtype_variant("Stratification", 0, "TraceAttribute", key)
+            }
+            Self::Custom(_) => serializer.serialize_unit_variant("Stratification", 1, "Custom"),
+        }
+    }
+}
+
+impl<'a> Stratification<'a> {
+    /// Stratify by the value of the trace attribute `key`.
+    pub fn trace_attribute(key: impl Into<String>) -> Self {
+        Self::TraceAttribute(key.into())
+    }
+
+    /// Stratify by the key computed by `stratum` for each trace.
+    pub fn by(stratum: impl Fn(&Trace) -> String + 'a) -> Self {
+        Self::Custom(Box::new(stratum))
+    }
+
+    /// The stratum key of a trace. For a [`Stratification::TraceAttribute`], the
+    /// canonical string form of its value (see [`coerce_to_string`]), so that, e.g.,
+    /// `Int(1)` and `String("1")` form the same stratum.
+    pub fn stratum(&self, trace: &Trace) -> AttributeResult<String> {
+        match self {
+            Self::TraceAttribute(key) => {
+                let attribute = trace.get_attribute_by_key(key)?;
+                coerce_to_string(&attribute.value).ok_or_else(|| {
+                    AttributeError::new(
+                        AttributeLevel::Trace,
+                        key,
+                        AttributeErrorKind::TypeMismatch(
+                            "String, Int, Float, Boolean, Date or ID".to_string(),
+                            attribute.value.clone(),
+                        ),
+                    )
+                })
+            }
+            Self::Custom(stratum) => Ok(stratum(trace)),
+        }
+    }
+
+    /// Assign the traces of both event logs to strata.
+    ///
+    /// Returns an `Err` if a trace does not have the trace attribute of a
+    /// [`Stratification::TraceAttribute`], or if its value is not primitive.
+    pub fn strata(&self, log_1: &EventLog, log_2: &EventLog) -> AttributeResult<Strata> {
+        let mut labels: Vec<String> = Vec::new();
+        let mut indices: HashMap<String, usize> = HashMap::new();
+        let mut assign = |log: &EventLog| -> AttributeResult<Vec<usize>> {
+            log.traces
+                .iter()
+                .map(|trace| {
+                    let key = self.stratum(trace)?;
+                    Ok(*indices.entry(key).or_insert_with_key(|key| {
+                        labels.push(key.clone());
+                        labels.len() - 1
+                    }))
+                })
+                .collect()
+        };
+        let behavior_1 = assign(log_1)?;
+        let behavior_2 = assign(log_2)?;
+        Ok(Strata {
+            labels,
+            behavior_1,
+            behavior_2,
+        })
+    }
+}
+
+/// The assignment of the cases of two event logs to strata.
+///
+/// The strata are given in the order of the traces, which must be the order of the
+/// representations extracted by the comparator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Strata {
+    /// The key of each stratum.
+    pub labels: Vec<String>,
+    /// The stratum (index into `labels`) of each case of the first event log.
+    pub behavior_1: Vec<usize>,
+    /// The stratum (index into `labels`) of each case of the second event log.
+    pub behavior_2: Vec<usize>,
+}
+
+impl Strata {
+    /// The number of cases of the first and second event log in each stratum.
+    pub fn sizes(&self) -> Vec<(usize, usize)> {
+        let mut sizes = vec![(0, 0); self.labels.len()];
+        self.behavior_1.iter().for_each(|s| sizes[*s].0 += 1);
+        self.behavior_2.iter().for_each(|s| sizes[*s].1 += 1);
+        sizes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comparators::permutation_test::{
+            control_flow::ControlFlowPermutationComparator,
+            permutation_test_comparator::PermutationTestComparator,
+        },
+        stats::pvalue::PValueOptions,
+        utils::attributes::add_or_overwrite_attribute,
+    };
+    use process_mining::event_log::AttributeValue;
+    use process_mining_macros::event_log;
+
+    fn set_order_type(log: &mut EventLog, express: usize) {
+        log.traces.iter_mut().enumerate().for_each(|(i, trace)| {
+            let order_type = if i < express { "express" } else { "standard" };
+            add_or_overwrite_attribute(
+                trace,
+                "order_type",
+                AttributeValue::String(order_type.to_string()),
+            );
+        });
+    }
+
+    #[test]
+    fn test_stratified_permutation_controls_for_case_mix() {
+        // Express orders skip activity b. The second event log has more express orders,
+        // but the behavior within both strata is the same.
+        let mut log_1 = event_log!(
+            [a, c], [a, c], [a, b, c], [a, b, c], [a, b, c], [a, b, c], [a, b, c], [a, b, c];
+            base_timestamp=EPOCH
+        );
+        let mut log_2 = event_log!(
+            [a, c], [a, c], [a, c], [a, c], [a, c], [a, c], [a, b, c], [a, b, c];
+            base_timestamp=EPOCH
+        );
+        set_order_type(&mut log_1, 2);
+        set_order_type(&mut log_2, 6);
+
+        let stratification = Stratification::trace_attribute("order_type");
+        let strata = stratification.strata(&log_1, &log_2).unwrap();
+        assert_eq!(strata.labels, vec!["express", "standard"]);
+        assert_eq!(strata.sizes(), vec![(2, 6), (6, 2)]);
+
+        let result = ControlFlowPermutationComparator
+            .compare_stratified(
+                &log_1,
+                &log_2,
+                200,
+                &stratification,
+                &PValueOptions::default(),
+                Some(42),
+            )
+            .unwrap();
+        // Within the strata, all permutations have the same EMD as the original logs
+        assert!(result.logs_emd > 0.0);
+        assert!(result
+            .permutation_emds
+            .iter()
+            .all(|emd| (emd - result.logs_emd).abs() < 1e-9));
+        assert_eq!(
+            result.metadata.parameters.get("stratification"),
+            Some(&serde_json::json!({ "TraceAttribute": "order_type" }))
+        );
+    }
+
+    #[test]
+    fn test_strata_of_differently_typed_values() {
+        let mut log_1 = event_log!([a], [a]; base_timestamp=EPOCH);
+        let mut log_2 = event_log!([a]; base_timestamp=EPOCH);
+        add_or_overwrite_attribute(&mut log_1.traces[0], "priority", AttributeValue::Int(1));
+        add_or_overwrite_attribute(&mut log_1.traces[1], "priority", AttributeValue::Int(2));
+        add_or_overwrite_attribute(
+            &mut log_2.traces[0],
+            "priority",
+            AttributeValue::String("1".to_string()),
+        );
+
+        let strata = Stratification::trace_attribute("priority")
+            .strata(&log_1, &log_2)
+            .unwrap();
+        assert_eq!(strata.labels, vec!["1", "2"]);
+        assert_eq!(strata.sizes(), vec![(1, 1), (1, 0)]);
+    }
+}
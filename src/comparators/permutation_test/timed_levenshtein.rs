@@ -1,12 +1,14 @@
 use process_mining::EventLog;
+use serde::Serialize;
 
 use crate::{
     binning::{
         kmeans_binner::{KMeansArgs, KMeansBinner},
         BinnerManager,
     },
-    comparators::common::extraction::{
-        apply_binner_manager_on_service_time_traces, extract_service_time_traces,
+    comparators::common::{
+        extraction::{apply_binner_manager_on_service_time_traces, extract_service_time_traces},
+        result::{config_parameters, ComparatorParameters},
     },
     distance::weighted_levenshtein::postnormalized_weighted_levenshtein_distance,
     utils::attributes::attribute_error::AttributeResult,
@@ -17,7 +19,7 @@ use super::permutation_test_comparator::PermutationTestComparator;
 /// An implementation of the [`PermutationTestComparator`] for timed control flow
 /// comparisons using K-Means++ clustering to bin service times, and the postnormalized
 /// weighted Levenshtein distance as a distance notion between _service-time traces_.
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Serialize)]
 pub struct TimedLevenshteinPermutationComparator {
     binner_args: KMeansArgs,
 }
@@ -57,4 +59,8 @@ impl PermutationTestComparator<Vec<(String, usize)>> for TimedLevenshteinPermuta
     fn cost(&self, rep_1: &Vec<(String, usize)>, rep_2: &Vec<(String, usize)>) -> f64 {
         postnormalized_weighted_levenshtein_distance(rep_1, rep_2)
     }
+
+    fn parameters(&self) -> ComparatorParameters {
+        config_parameters(self)
+    }
 }
@@ -0,0 +1,63 @@
+use crate::representation::ProjectedValue;
+
+/// The distance between two [`ProjectedValue`]s, scaled to `[0, 1]`.
+///
+/// Numeric values (which are already scaled to `[0, 1]`) have their absolute
+/// difference as distance. Categorical values have a distance of 0 if they are equal,
+/// and 1 otherwise. Two missing values have a distance of 0. Values of different
+/// kinds (including a missing and a present value) have a distance of 1.
+pub fn projected_value_distance(value_1: &ProjectedValue, value_2: &ProjectedValue) -> f64 {
+    match (value_1, value_2) {
+        (ProjectedValue::Numeric(x), ProjectedValue::Numeric(y)) => (x.0 - y.0).abs().min(1.0),
+        (ProjectedValue::Categorical(x), ProjectedValue::Categorical(y)) if x == y => 0.0,
+        (ProjectedValue::Missing, ProjectedValue::Missing) => 0.0,
+        _ => 1.0,
+    }
+}
+
+/// A weighted Gower-like distance between two tuples of [`ProjectedValue`]s. Computed
+/// as the weighted mean of the distances between the values at each position
+/// (see [`projected_value_distance`]).
+///
+/// `weights` is expected to contain a weight for each position. If the weights
+/// sum to 0, the distance is 0.
+pub fn weighted_gower_distance(
+    values_1: &[ProjectedValue],
+    values_2: &[ProjectedValue],
+    weights: &[f64],
+) -> f64 {
+    let total_weight: f64 = weights.iter().sum();
+    if total_weight == 0.0 {
+        return 0.0;
+    }
+
+    values_1
+        .iter()
+        .zip(values_2.iter())
+        .zip(weights.iter())
+        .map(|((value_1, value_2), weight)| weight * projected_value_distance(value_1, value_2))
+        .sum::<f64>()
+        / total_weight
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::ordered_float::OrderedFloat;
+
+    #[test]
+    fn test_weighted_gower_distance() {
+        let case_1 = vec![
+            ProjectedValue::Numeric(OrderedFloat(0.0)),
+            ProjectedValue::Categorical("web".into()),
+        ];
+        let case_2 = vec![
+            ProjectedValue::Numeric(OrderedFloat(0.5)),
+            ProjectedValue::Categorical("store".into()),
+        ];
+
+        assert_eq!(weighted_gower_distance(&case_1, &case_1, &[1.0, 1.0]), 0.0);
+        assert_eq!(weighted_gower_distance(&case_1, &case_2, &[1.0, 1.0]), 0.75);
+        assert_eq!(weighted_gower_distance(&case_1, &case_2, &[2.0, 0.0]), 0.5);
+    }
+}
@@ -0,0 +1,162 @@
+//! Distance matrices between variants: Either dense ([`Array2`]), or lazily computed
+//! on demand ([`LazyDistanceMatrix`]). With a bounded memo, the latter also handles
+//! variant sets too large to hold all pairwise distances in memory.
+
+use std::{cell::RefCell, collections::HashMap, fmt::Debug};
+
+use ndarray::Array2;
+
+/// A matrix of distances between two collections of variants (rows and columns).
+pub trait DistanceMatrix: Debug {
+    /// The number of rows and columns.
+    fn shape(&self) -> (usize, usize);
+
+    /// The distance between the variant of row `row` and the variant of column `col`.
+    fn get(&self, row: usize, col: usize) -> f64;
+
+    /// Materialize the submatrix of the given `rows` and `cols`, e.g., to compute
+    /// the EMD between two samples.
+    fn select(&self, rows: &[usize], cols: &[usize]) -> Array2<f64> {
+        Array2::from_shape_fn((rows.len(), cols.len()), |(i, j)| {
+            self.get(rows[i], cols[j])
+        })
+    }
+}
+
+impl DistanceMatrix for Array2<f64> {
+    fn shape(&self) -> (usize, usize) {
+        self.dim()
+    }
+
+    fn get(&self, row: usize, col: usize) -> f64 {
+        self[(row, col)]
+    }
+
+    fn select(&self, rows: &[usize], cols: &[usize]) -> Array2<f64> {
+        ndarray::ArrayBase::select(self, ndarray::Axis(0), rows).select(ndarray::Axis(1), cols)
+    }
+}
+
+/// A cost function between two variants.
+pub type CostFn<'a, T> = Box<dyn Fn(&T, &T) -> f64 + 'a>;
+
+/// A distance matrix whose entries are only computed when they are accessed, and
+/// memoized to not compute them again. Only the distances between variants that
+/// actually occur together (e.g., in a permutation) are computed.
+///
+/// The memoized distances can be bounded using [`with_memo_limit`](LazyDistanceMatrix::with_memo_limit);
+/// Once the limit is reached, further distances are computed on every access.
+pub struct LazyDistanceMatrix<'a, T> {
+    rows: Vec<T>,
+    /// The column variants. `None` if the matrix is symmetric, i.e., the columns are
+    /// the rows.
+    cols: Option<Vec<T>>,
+    cost: CostFn<'a, T>,
+    memo: RefCell<HashMap<(usize, usize), f64>>,
+    memo_limit: Option<usize>,
+}
+
+impl<T: Debug> Debug for LazyDistanceMatrix<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyDistanceMatrix")
+            .field("shape", &self.shape())
+            .field("symmetric", &self.cols.is_none())
+            .field("memoized", &self.num_memoized())
+            .field("memo_limit", &self.memo_limit)
+            .finish()
+    }
+}
+
+impl<'a, T> LazyDistanceMatrix<'a, T> {
+    /// A lazy matrix of the distances between `rows` and `cols`.
+    pub fn new(rows: Vec<T>, cols: Vec<T>, cost: CostFn<'a, T>) -> Self {
+        Self {
+            rows,
+            cols: Some(cols),
+            cost,
+            memo: RefCell::new(HashMap::new()),
+            memo_limit: None,
+        }
+    }
+
+    /// A lazy matrix of the distances between all pairs of `variants`. The cost is
+    /// assumed to be symmetric, so each distance is only computed once.
+    pub fn symmetric(variants: Vec<T>, cost: CostFn<'a, T>) -> Self {
+        Self {
+            rows: variants,
+            cols: None,
+            cost,
+            memo: RefCell::new(HashMap::new()),
+            memo_limit: None,
+        }
+    }
+
+    /// Memoize at most `memo_limit` distances.
+    pub fn with_memo_limit(mut self, memo_limit: usize) -> Self {
+        self.memo_limit = Some(memo_limit);
+        self
+    }
+
+    /// The number of memoized distances.
+    pub fn num_memoized(&self) -> usize {
+        self.memo.borrow().len()
+    }
+}
+
+impl<T: Debug> DistanceMatrix for LazyDistanceMatrix<'_, T> {
+    fn shape(&self) -> (usize, usize) {
+        let cols = self.cols.as_ref().unwrap_or(&self.rows);
+        (self.rows.len(), cols.len())
+    }
+
+    fn get(&self, row: usize, col: usize) -> f64 {
+        let key = match self.cols {
+            None if col < row => (col, row),
+            _ => (row, col),
+        };
+        if let Some(distance) = self.memo.borrow().get(&key) {
+            return *distance;
+        }
+
+        let cols = self.cols.as_ref().unwrap_or(&self.rows);
+        let distance = (self.cost)(&self.rows[key.0], &cols[key.1]);
+        let mut memo = self.memo.borrow_mut();
+        if !matches!(self.memo_limit, Some(limit) if memo.len() >= limit) {
+            memo.insert(key, distance);
+        }
+        distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_lazy_distance_matrix() {
+        let evaluations = Cell::new(0);
+        let cost = |a: &i32, b: &i32| {
+            evaluations.set(evaluations.get() + 1);
+            (a - b).abs() as f64
+        };
+        let lazy = LazyDistanceMatrix::symmetric(vec![1, 2, 4, 8], Box::new(cost));
+        let dense = Array2::from_shape_fn((4, 4), |(i, j)| lazy.get(i, j));
+        assert_eq!(dense[(0, 3)], 7.0);
+        assert_eq!(dense[(3, 0)], 7.0);
+        // Each unordered pair is only computed once
+        assert_eq!(evaluations.get(), 10);
+
+        assert_eq!(
+            lazy.select(&[1, 3], &[0]),
+            DistanceMatrix::select(&dense, &[1, 3], &[0])
+        );
+        assert_eq!(evaluations.get(), 10);
+
+        let limited =
+            LazyDistanceMatrix::new(vec![1, 2], vec![3], Box::new(cost)).with_memo_limit(1);
+        limited.get(0, 0);
+        limited.get(1, 0);
+        assert_eq!(limited.num_memoized(), 1);
+    }
+}
@@ -1 +1,3 @@
+pub mod gower;
+pub mod matrix;
 pub mod weighted_levenshtein;
@@ -1,6 +1,13 @@
 use std::cmp::max;
 
 use ndarray::Array2;
+use serde::Serialize;
+
+use crate::{
+    distance::gower::projected_value_distance,
+    representation::{ProjectedValue, ServiceWaitingTimeEvent},
+    utils::ordered_float::OrderedFloat,
+};
 
 /// Trait to define the edit costs for the weighted Levenshtein distance.
 ///
@@ -12,11 +19,43 @@ pub trait LevenshteinDistance: PartialEq {
     fn substitution_cost(&self, other: &Self) -> f64;
 }
 
+/// Edit costs for the weighted Levenshtein distance that depend on a configuration
+/// (e.g., weights) besides the events themselves. See [`weighted_levenshtein_distance_with`].
+pub trait EditCosts<T> {
+    fn insertion_cost(&self, event: &T) -> f64;
+    fn deletion_cost(&self, event: &T) -> f64;
+    fn substitution_cost(&self, event_1: &T, event_2: &T) -> f64;
+}
+
+/// The edit costs defined by the events' [`LevenshteinDistance`] implementation.
+struct IntrinsicCosts;
+
+impl<T: LevenshteinDistance> EditCosts<T> for IntrinsicCosts {
+    fn insertion_cost(&self, event: &T) -> f64 {
+        event.insertion_cost()
+    }
+    fn deletion_cost(&self, event: &T) -> f64 {
+        event.deletion_cost()
+    }
+    fn substitution_cost(&self, event_1: &T, event_2: &T) -> f64 {
+        event_1.substitution_cost(event_2)
+    }
+}
+
 /// The weighted Levenshtein distance using edit costs defined with the
 /// [`LevenshteinDistance`] trait.
 pub fn weighted_levenshtein_distance<T>(trace_1: &[T], trace_2: &[T]) -> f64
 where
     T: LevenshteinDistance,
+{
+    weighted_levenshtein_distance_with(trace_1, trace_2, &IntrinsicCosts)
+}
+
+/// The weighted Levenshtein distance using the given [`EditCosts`].
+pub fn weighted_levenshtein_distance_with<T, C>(trace_1: &[T], trace_2: &[T], costs: &C) -> f64
+where
+    T: PartialEq,
+    C: EditCosts<T>,
 {
     if trace_1 == trace_2 {
         return 0.0;
@@ -29,17 +68,17 @@ where
     // characters to match the strings if the other string is empty
     let mut matrix: Array2<f64> = Array2::zeros((len_1 + 1, len_2 + 1));
     for i in 1..=len_1 {
-        matrix[(i, 0)] = matrix[(i - 1, 0)] + trace_1[i - 1].insertion_cost();
+        matrix[(i, 0)] = matrix[(i - 1, 0)] + costs.insertion_cost(&trace_1[i - 1]);
     }
     for j in 1..=len_2 {
-        matrix[(0, j)] = matrix[(0, j - 1)] + trace_2[j - 1].insertion_cost();
+        matrix[(0, j)] = matrix[(0, j - 1)] + costs.insertion_cost(&trace_2[j - 1]);
     }
 
     trace_1.iter().enumerate().for_each(|(i, event_1)| {
         trace_2.iter().enumerate().for_each(|(j, event_2)| {
-            let deletion_cost = event_1.deletion_cost();
-            let insertion_cost = event_2.insertion_cost();
-            let substitution_cost = event_1.substitution_cost(event_2);
+            let deletion_cost = costs.deletion_cost(event_1);
+            let insertion_cost = costs.insertion_cost(event_2);
+            let substitution_cost = costs.substitution_cost(event_1, event_2);
             matrix[(i + 1, j + 1)] = triple_min_f64(
                 matrix[(i, j + 1)] + deletion_cost,  // deletion
                 matrix[(i + 1, j)] + insertion_cost, // insertion
@@ -53,13 +92,31 @@ where
 
 /// The postnormalized weighted Levenshtein distance using edit costs defined
 /// with the [`LevenshteinDistance`] trait. Computed as the weighted Levenshtein
-/// distance divided by the length of the longer trace.
+/// distance divided by the length of the longer trace. Two empty traces have a
+/// distance of 0.
 pub fn postnormalized_weighted_levenshtein_distance<T>(trace_1: &[T], trace_2: &[T]) -> f64
 where
     T: LevenshteinDistance,
 {
-    let length: f64 = max(trace_1.len(), trace_2.len()) as f64;
-    let distance = weighted_levenshtein_distance(trace_1, trace_2);
+    postnormalized_weighted_levenshtein_distance_with(trace_1, trace_2, &IntrinsicCosts)
+}
+
+/// The postnormalized weighted Levenshtein distance using the given [`EditCosts`].
+/// See [`postnormalized_weighted_levenshtein_distance`].
+pub fn postnormalized_weighted_levenshtein_distance_with<T, C>(
+    trace_1: &[T],
+    trace_2: &[T],
+    costs: &C,
+) -> f64
+where
+    T: PartialEq,
+    C: EditCosts<T>,
+{
+    let length = max(trace_1.len(), trace_2.len());
+    if length == 0 {
+        return 0.0;
+    }
+    let distance = weighted_levenshtein_distance_with(trace_1, trace_2, costs);
 
     distance / length as f64
 }
@@ -133,6 +190,121 @@ impl LevenshteinDistance for (String, usize) {
     }
 }
 
+impl LevenshteinDistance for (String, String) {
+    fn insertion_cost(&self) -> f64 {
+        1.0
+    }
+    fn deletion_cost(&self) -> f64 {
+        1.0
+    }
+    fn substitution_cost(&self, other: &Self) -> f64 {
+        let first_cost = if self.0 == other.0 { 0.0 } else { 1.0 };
+        let second_cost = if self.1 == other.1 { 0.0 } else { 1.0 };
+        0.5 * (first_cost + second_cost)
+    }
+}
+
+/// The weights of the components of a [`ServiceWaitingTimeEvent`] in the weighted
+/// Levenshtein distance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct ServiceWaitingTimeWeights {
+    pub activity: OrderedFloat,
+    pub service_time: OrderedFloat,
+    pub waiting_time: OrderedFloat,
+}
+
+impl ServiceWaitingTimeWeights {
+    pub fn new(activity: f64, service_time: f64, waiting_time: f64) -> Self {
+        Self {
+            activity: OrderedFloat(activity),
+            service_time: OrderedFloat(service_time),
+            waiting_time: OrderedFloat(waiting_time),
+        }
+    }
+
+    /// The sum of all weights, i.e., the maximal cost of an edit operation.
+    pub fn total(&self) -> f64 {
+        self.activity.0 + self.service_time.0 + self.waiting_time.0
+    }
+}
+
+impl Default for ServiceWaitingTimeWeights {
+    /// Weigh the activity with 0.5, and the service and waiting time with 0.25
+    /// each.
+    fn default() -> Self {
+        Self::new(0.5, 0.25, 0.25)
+    }
+}
+
+/// The edit costs of [`ServiceWaitingTimeEvent`]s: Inserting or deleting an event
+/// costs the sum of all weights, i.e., the maximal substitution cost. Substituting an
+/// event costs the weighted sum of the component costs, where differing activities
+/// cost `1`, and the bin differences are scaled to `[0, 1]` using the largest bin
+/// index `max_bin`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ServiceWaitingTimeCosts {
+    pub weights: ServiceWaitingTimeWeights,
+    pub max_bin: usize,
+}
+
+impl ServiceWaitingTimeCosts {
+    pub fn new(weights: ServiceWaitingTimeWeights, max_bin: usize) -> Self {
+        Self { weights, max_bin }
+    }
+}
+
+impl EditCosts<ServiceWaitingTimeEvent> for ServiceWaitingTimeCosts {
+    fn insertion_cost(&self, _event: &ServiceWaitingTimeEvent) -> f64 {
+        self.weights.total()
+    }
+    fn deletion_cost(&self, _event: &ServiceWaitingTimeEvent) -> f64 {
+        self.weights.total()
+    }
+    fn substitution_cost(
+        &self,
+        event_1: &ServiceWaitingTimeEvent,
+        event_2: &ServiceWaitingTimeEvent,
+    ) -> f64 {
+        let activity_cost = if event_1.activity == event_2.activity {
+            0.0
+        } else {
+            1.0
+        };
+        let scale = self.max_bin.max(1) as f64;
+        let service_time_cost = event_1.service_time.abs_diff(event_2.service_time) as f64 / scale;
+        let waiting_time_cost = event_1.waiting_time.abs_diff(event_2.waiting_time) as f64 / scale;
+
+        self.weights.activity.0 * activity_cost
+            + self.weights.service_time.0 * service_time_cost
+            + self.weights.waiting_time.0 * waiting_time_cost
+    }
+}
+
+/// Events projected on their attributes (see [`EventAttributeProjection`]). Inserting
+/// or deleting an event costs 1, substituting an event costs the (unweighted) Gower
+/// distance between the attribute tuples.
+///
+/// [`EventAttributeProjection`]: crate::comparators::common::extraction::EventAttributeProjection
+impl LevenshteinDistance for Vec<ProjectedValue> {
+    fn insertion_cost(&self) -> f64 {
+        1.0
+    }
+    fn deletion_cost(&self) -> f64 {
+        1.0
+    }
+    fn substitution_cost(&self, other: &Self) -> f64 {
+        let num_attributes = self.len().max(other.len());
+        if num_attributes == 0 {
+            return 0.0;
+        }
+        self.iter()
+            .zip(other.iter())
+            .map(|(value_1, value_2)| projected_value_distance(value_1, value_2))
+            .sum::<f64>()
+            / num_attributes as f64
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,4 +361,50 @@ mod tests {
             2.0 / 4.0
         )
     }
+
+    #[test]
+    fn test_activity_resource_lev_distance() {
+        let trace_1: Vec<(String, String)> = vec![
+            ("a".into(), "alice".into()),
+            ("b".into(), "bob".into()),
+            ("c".into(), "alice".into()),
+        ];
+        let trace_2: Vec<(String, String)> = vec![
+            ("a".into(), "alice".into()),
+            ("b".into(), "alice".into()),
+            ("d".into(), "bob".into()),
+        ];
+        // Substitute the resource of b (0.5) and both activity and resource of c (1.0)
+        assert_eq!(weighted_levenshtein_distance(&trace_1, &trace_2), 1.5);
+    }
+
+    #[test]
+    fn test_service_waiting_time_event_costs() {
+        let event =
+            |activity: &str, service_time: usize, waiting_time: usize| ServiceWaitingTimeEvent {
+                activity: activity.to_string(),
+                service_time,
+                waiting_time,
+            };
+        let costs =
+            ServiceWaitingTimeCosts::new(ServiceWaitingTimeWeights::new(0.5, 0.25, 0.25), 2);
+
+        assert_eq!(costs.insertion_cost(&event("a", 0, 0)), 1.0);
+        // Only the waiting time changed
+        assert_eq!(
+            costs.substitution_cost(&event("a", 1, 0), &event("a", 1, 2)),
+            0.25
+        );
+        assert_eq!(
+            costs.substitution_cost(&event("a", 1, 0), &event("b", 0, 1)),
+            0.75
+        );
+
+        let trace_1 = vec![event("a", 1, 0), event("b", 1, 0)];
+        let trace_2 = vec![event("a", 1, 0), event("b", 1, 1)];
+        assert_eq!(
+            postnormalized_weighted_levenshtein_distance_with(&trace_1, &trace_2, &costs),
+            0.125 / 2.0
+        );
+    }
 }
@@ -0,0 +1,517 @@
+//! Concept drift detection: Find the points in time at which the process changed by
+//! comparing adjacent windows of a single event log.
+//!
+//! The traces are ordered by their start time (their earliest `time:timestamp`).
+//! For each boundary between windows, the `size` traces (or the traces started in
+//! the time span `size`) before the boundary are compared to those after it using
+//! the permutation test. The boundaries move by `step`; With `step == size`, the
+//! windows are consecutive.
+//!
+//! The representations are extracted and the distances between them are computed
+//! only once for the whole event log, and reused for all window comparisons.
+
+use std::{fmt::Debug, hash::Hash};
+
+use chrono::{DateTime, FixedOffset, TimeDelta};
+use process_mining::{event_log::Trace, EventLog};
+
+use crate::{
+    comparators::{
+        common::{comparison_error::ComparisonError, preparation::log_with_traces},
+        permutation_test::permutation_test_comparator::{
+            compare_with_variant_distances, PermutationTestComparator,
+            PermutationTestComparisonResult,
+        },
+    },
+    stats::{
+        correction::{CorrectedPValues, CorrectionMethod},
+        pvalue::PValueOptions,
+        sequential::SamplingMode,
+    },
+    utils::attributes::{attribute_error::AttributeResult, get_complete_timestamp},
+};
+
+/// How an event log is split into windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Windowing {
+    /// Windows of `size` traces. The boundary between the windows moves by `step`
+    /// traces.
+    Traces { size: usize, step: usize },
+    /// Windows containing the traces started in a time span of `size`. The boundary
+    /// between the windows moves by `step`.
+    Time { size: TimeDelta, step: TimeDelta },
+}
+
+impl Windowing {
+    /// Consecutive, non-overlapping windows of `size` traces.
+    pub fn consecutive_traces(size: usize) -> Self {
+        Self::Traces { size, step: size }
+    }
+
+    /// Consecutive, non-overlapping windows spanning `size` time.
+    pub fn consecutive_time(size: TimeDelta) -> Self {
+        Self::Time { size, step: size }
+    }
+}
+
+/// Options for drift detection.
+#[derive(Debug, Clone)]
+pub struct DriftOptions {
+    windowing: Windowing,
+    distribution_size: usize,
+    alpha: f64,
+    pvalue_options: PValueOptions,
+    min_window_size: usize,
+    correction: Option<CorrectionMethod>,
+    seed: Option<u64>,
+}
+
+impl DriftOptions {
+    /// Compare adjacent windows using 1000 permutations each, detecting change points
+    /// at a significance level of 0.05.
+    pub fn new(windowing: Windowing) -> Self {
+        Self {
+            windowing,
+            distribution_size: 1000,
+            alpha: 0.05,
+            pvalue_options: PValueOptions::default(),
+            min_window_size: 1,
+            correction: None,
+            seed: None,
+        }
+    }
+
+    /// The number of permutations per window comparison.
+    pub fn with_distribution_size(mut self, distribution_size: usize) -> Self {
+        self.distribution_size = distribution_size;
+        self
+    }
+
+    /// The significance level for change points: A comparison is significant if its
+    /// (adjusted) p-value is at most `alpha`. Note that many windows are compared,
+    /// so some false positives are to be expected unless a correction is used (see
+    /// [`DriftOptions::with_correction`]).
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn with_pvalue_options(mut self, pvalue_options: PValueOptions) -> Self {
+        self.pvalue_options = pvalue_options;
+        self
+    }
+
+    /// Skip comparisons where a window contains fewer than `min_window_size` traces.
+    /// Mostly relevant for [`Windowing::Time`].
+    pub fn with_min_window_size(mut self, min_window_size: usize) -> Self {
+        self.min_window_size = min_window_size.max(1);
+        self
+    }
+
+    /// Detect change points using p-values adjusted for multiple testing, at the
+    /// FWER or FDR level `alpha`. As adjacent comparisons share windows, the tests
+    /// are dependent; [`CorrectionMethod::BenjaminiYekutieli`] accounts for that.
+    pub fn with_correction(mut self, correction: CorrectionMethod) -> Self {
+        self.correction = Some(correction);
+        self
+    }
+
+    /// Seed the permutation tests. The comparison of the `i`-th pair of windows uses
+    /// the seed `seed + i`, so that the test distributions are independent.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+/// A window of traces.
+#[derive(Debug, Clone)]
+pub struct Window {
+    /// The indices of the traces in the window (in the event log), ordered by start time.
+    pub trace_indices: Vec<usize>,
+    /// The start time of the first trace in the window.
+    pub start: DateTime<FixedOffset>,
+    /// The start time of the last trace in the window.
+    pub end: DateTime<FixedOffset>,
+}
+
+/// The comparison of two adjacent windows.
+#[derive(Debug)]
+pub struct WindowComparison {
+    /// The boundary between the two windows. For [`Windowing::Traces`], this is the
+    /// start time of the first trace of the second window.
+    pub boundary: DateTime<FixedOffset>,
+    pub window_1: Window,
+    pub window_2: Window,
+    pub result: PermutationTestComparisonResult,
+}
+
+/// A detected change in the process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangePoint {
+    /// The index of the comparison in [`DriftResult::comparisons`].
+    pub comparison_index: usize,
+    /// The boundary between the windows at which the change was detected.
+    pub boundary: DateTime<FixedOffset>,
+    pub logs_emd: f64,
+    /// The p-value `(b+1)/(m+1)` of the comparison, adjusted if a correction is used.
+    pub pvalue: f64,
+}
+
+/// The result of drift detection.
+#[derive(Debug)]
+pub struct DriftResult {
+    /// The comparisons of adjacent windows, ordered by boundary.
+    pub comparisons: Vec<WindowComparison>,
+    /// The detected change points. Within each run of consecutive significant
+    /// comparisons, the comparison with the largest EMD is the change point.
+    pub change_points: Vec<ChangePoint>,
+}
+
+impl DriftResult {
+    /// The time series of `(boundary, EMD)` pairs.
+    pub fn emds(&self) -> Vec<(DateTime<FixedOffset>, f64)> {
+        self.comparisons
+            .iter()
+            .map(|comparison| (comparison.boundary, comparison.result.logs_emd))
+            .collect()
+    }
+
+    /// The time series of `(boundary, p-value)` pairs, using the p-value `(b+1)/(m+1)`.
+    pub fn pvalues(&self) -> Vec<(DateTime<FixedOffset>, f64)> {
+        self.comparisons
+            .iter()
+            .map(|comparison| {
+                (
+                    comparison.boundary,
+                    comparison.result.pvalue_estimate.unbiased,
+                )
+            })
+            .collect()
+    }
+
+    /// Adjust the p-values of the comparisons for multiple testing.
+    pub fn correct(&self, method: CorrectionMethod, level: f64) -> CorrectedPValues {
+        CorrectedPValues::from_results(
+            self.comparisons.iter().map(|comparison| &comparison.result),
+            method,
+            level,
+        )
+    }
+}
+
+/// Detect concept drift in an event log by comparing adjacent windows with the
+/// permutation test of the `comparator`.
+///
+/// Traces without events have no start time and are ignored. Returns an `Err` if
+/// an event has no `time:timestamp`, if the comparator fails to extract the
+/// representations, or if it does not extract one representation per trace.
+pub fn detect_drift<T, C>(
+    comparator: &C,
+    log: &EventLog,
+    options: &DriftOptions,
+) -> Result<DriftResult, ComparisonError>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: PermutationTestComparator<T> + ?Sized,
+{
+    let start_times = log
+        .traces
+        .iter()
+        .map(trace_start_time)
+        .collect::<AttributeResult<Vec<_>>>()?;
+
+    // Extract the representations of all traces at once, so that binning etc. is
+    // consistent across the windows
+    let (representations, _) =
+        comparator.extract_representations(log, &log_with_traces(log, Vec::new()))?;
+    // The windows refer to the traces, so each trace needs its representation
+    ComparisonError::check_representation_count(log.traces.len(), representations.len())?;
+
+    let mut ordered: Vec<(usize, DateTime<FixedOffset>)> = start_times
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, start)| start.map(|start| (index, start)))
+        .collect();
+    ordered.sort_by_key(|(_, start)| *start);
+
+    let ordered_representations: Vec<T> = ordered
+        .iter()
+        .map(|(index, _)| representations[*index].clone())
+        .collect();
+    let variant_distances = comparator.compute_variant_distances(&ordered_representations, &[]);
+
+    let comparisons: Vec<WindowComparison> = window_pairs(&ordered, &options.windowing)
+        .into_iter()
+        .filter(|(_, (start_1, end_1), (start_2, end_2))| {
+            end_1 - start_1 >= options.min_window_size && end_2 - start_2 >= options.min_window_size
+        })
+        .enumerate()
+        .map(|(index, (boundary, range_1, range_2))| {
+            let window = |(start, end): (usize, usize)| Window {
+                trace_indices: ordered[start..end].iter().map(|(i, _)| *i).collect(),
+                start: ordered[start].1,
+                end: ordered[end - 1].1,
+            };
+            let result = compare_with_variant_distances(
+                comparator,
+                &variant_distances,
+                ordered_representations[range_1.0..range_1.1].to_vec(),
+                ordered_representations[range_2.0..range_2.1].to_vec(),
+                None,
+                options.distribution_size,
+                SamplingMode::Fixed(&options.pvalue_options),
+                options.seed.map(|seed| seed.wrapping_add(index as u64)),
+            );
+            WindowComparison {
+                boundary,
+                window_1: window(range_1),
+                window_2: window(range_2),
+                result,
+            }
+        })
+        .collect();
+
+    let (pvalues, significant) = match options.correction {
+        Some(method) => {
+            let corrected = CorrectedPValues::from_results(
+                comparisons.iter().map(|comparison| &comparison.result),
+                method,
+                options.alpha,
+            );
+            (corrected.adjusted_pvalues, corrected.rejected)
+        }
+        None => {
+            let pvalues: Vec<f64> = comparisons
+                .iter()
+                .map(|comparison| comparison.result.pvalue_estimate.unbiased)
+                .collect();
+            // The same rule as the rejections of `CorrectedPValues`
+            let significant = pvalues.iter().map(|p| *p <= options.alpha).collect();
+            (pvalues, significant)
+        }
+    };
+    let change_points = find_change_points(&comparisons, &pvalues, &significant);
+    Ok(DriftResult {
+        comparisons,
+        change_points,
+    })
+}
+
+/// The start time of a trace, i.e., its earliest `time:timestamp`. `None` if the
+/// trace has no events.
+fn trace_start_time(trace: &Trace) -> AttributeResult<Option<DateTime<FixedOffset>>> {
+    trace.events.iter().map(get_complete_timestamp).try_fold(
+        None,
+        |earliest: Option<DateTime<FixedOffset>>, timestamp| {
+            let timestamp = timestamp?;
+            Ok(Some(earliest.map_or(timestamp, |t| t.min(timestamp))))
+        },
+    )
+}
+
+type WindowRange = (usize, usize);
+
+/// The boundaries and pairs of adjacent windows as ranges `[start, end)` of positions
+/// in `ordered` (the traces sorted by start time).
+fn window_pairs(
+    ordered: &[(usize, DateTime<FixedOffset>)],
+    windowing: &Windowing,
+) -> Vec<(DateTime<FixedOffset>, WindowRange, WindowRange)> {
+    let mut pairs = Vec::new();
+    match *windowing {
+        Windowing::Traces { size, step } => {
+            let (size, step) = (size.max(1), step.max(1));
+            let mut boundary = size;
+            while boundary + size <= ordered.len() {
+                pairs.push((
+                    ordered[boundary].1,
+                    (boundary - size, boundary),
+                    (boundary, boundary + size),
+                ));
+                boundary += step;
+            }
+        }
+        Windowing::Time { size, step } => {
+            if ordered.is_empty() || size <= TimeDelta::zero() {
+                return pairs;
+            }
+            let step = if step > TimeDelta::zero() { step } else { size };
+            let position =
+                |time: DateTime<FixedOffset>| ordered.partition_point(|(_, start)| *start < time);
+
+            let last = ordered[ordered.len() - 1].1;
+            let mut boundary = ordered[0].1 + size;
+            while boundary <= last {
+                pairs.push((
+                    boundary,
+                    (position(boundary - size), position(boundary)),
+                    (position(boundary), position(boundary + size)),
+                ));
+                boundary += step;
+            }
+        }
+    }
+    pairs
+}
+
+/// Find the change points: Within each run of consecutive significant comparisons,
+/// the comparison with the largest EMD.
+fn find_change_points(
+    comparisons: &[WindowComparison],
+    pvalues: &[f64],
+    significant: &[bool],
+) -> Vec<ChangePoint> {
+    let mut change_points = Vec::new();
+    let mut current: Option<ChangePoint> = None;
+
+    comparisons
+        .iter()
+        .zip(pvalues.iter().zip(significant))
+        .enumerate()
+        .for_each(|(index, (comparison, (&pvalue, &significant)))| {
+            if significant {
+                let candidate = ChangePoint {
+                    comparison_index: index,
+                    boundary: comparison.boundary,
+                    logs_emd: comparison.result.logs_emd,
+                    pvalue,
+                };
+                let is_larger = match &current {
+                    Some(best) => candidate.logs_emd > best.logs_emd,
+                    None => true,
+                };
+                if is_larger {
+                    current = Some(candidate);
+                }
+            } else if let Some(best) = current.take() {
+                change_points.push(best);
+            }
+        });
+    change_points.extend(current);
+    change_points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comparators::permutation_test::control_flow::ControlFlowPermutationComparator,
+        utils::{attributes::add_or_overwrite_attribute, constants::TIMESTAMP_KEY},
+    };
+    use process_mining::event_log::AttributeValue;
+    use process_mining_macros::event_log;
+
+    /// 10 traces `<a, b>` followed by 10 traces `<c, d>`, one trace started per day.
+    fn drifting_log() -> EventLog {
+        let mut log = event_log!([a, b], [c, d]; base_timestamp=EPOCH);
+        let (trace_ab, trace_cd) = (log.traces[0].clone(), log.traces[1].clone());
+        log.traces = [vec![trace_ab; 10], vec![trace_cd; 10]].concat();
+
+        log.traces.iter_mut().enumerate().for_each(|(day, trace)| {
+            trace
+                .events
+                .iter_mut()
+                .enumerate()
+                .for_each(|(hour, event)| {
+                    let timestamp = DateTime::UNIX_EPOCH.fixed_offset()
+                        + TimeDelta::days(day as i64)
+                        + TimeDelta::hours(hour as i64);
+                    add_or_overwrite_attribute(
+                        event,
+                        TIMESTAMP_KEY,
+                        AttributeValue::Date(timestamp),
+                    );
+                })
+        });
+        // Shuffle the traces in the log; The windows are based on the start times
+        log.traces.reverse();
+        log
+    }
+
+    #[test]
+    fn test_detect_drift_by_trace_count() {
+        let log = drifting_log();
+        let options = DriftOptions::new(Windowing::consecutive_traces(5))
+            .with_distribution_size(200)
+            .with_correction(CorrectionMethod::BenjaminiYekutieli)
+            .with_seed(42);
+        let drift = detect_drift(&ControlFlowPermutationComparator, &log, &options).unwrap();
+
+        assert_eq!(drift.comparisons.len(), 3);
+        assert_eq!(
+            drift.emds().iter().map(|(_, emd)| *emd).collect::<Vec<_>>(),
+            vec![0.0, 1.0, 0.0]
+        );
+        assert_eq!(drift.change_points.len(), 1);
+        assert_eq!(drift.change_points[0].comparison_index, 1);
+        assert_eq!(
+            drift.change_points[0].boundary,
+            DateTime::UNIX_EPOCH.fixed_offset() + TimeDelta::days(10)
+        );
+        assert_eq!(
+            drift
+                .comparisons
+                .iter()
+                .map(|comparison| comparison.result.metadata.seed)
+                .collect::<Vec<_>>(),
+            vec![Some(42), Some(43), Some(44)]
+        );
+    }
+
+    #[test]
+    fn test_change_points_at_alpha() {
+        let log = drifting_log();
+        let options = DriftOptions::new(Windowing::consecutive_traces(5))
+            .with_distribution_size(200)
+            .with_correction(CorrectionMethod::Bonferroni)
+            .with_seed(42);
+        let drift = detect_drift(&ControlFlowPermutationComparator, &log, &options).unwrap();
+        let adjusted_pvalue = drift.change_points[0].pvalue;
+
+        // An adjusted p-value equal to alpha is a change point, and rejected
+        let drift = detect_drift(
+            &ControlFlowPermutationComparator,
+            &log,
+            &options.clone().with_alpha(adjusted_pvalue),
+        )
+        .unwrap();
+        assert_eq!(drift.change_points.len(), 1);
+        assert_eq!(drift.change_points[0].pvalue, adjusted_pvalue);
+        let corrected = drift.correct(CorrectionMethod::Bonferroni, adjusted_pvalue);
+        assert!(corrected.rejected[drift.change_points[0].comparison_index]);
+
+        // Just below, neither
+        let alpha = adjusted_pvalue - 1e-12;
+        let drift = detect_drift(
+            &ControlFlowPermutationComparator,
+            &log,
+            &options.with_alpha(alpha),
+        )
+        .unwrap();
+        assert!(drift.change_points.is_empty());
+        let corrected = drift.correct(CorrectionMethod::Bonferroni, alpha);
+        assert_eq!(corrected.num_rejected(), 0);
+    }
+
+    #[test]
+    fn test_detect_drift_by_time() {
+        let log = drifting_log();
+        let options = DriftOptions::new(Windowing::Time {
+            size: TimeDelta::days(4),
+            step: TimeDelta::days(2),
+        })
+        .with_distribution_size(200)
+        .with_seed(42);
+        let drift = detect_drift(&ControlFlowPermutationComparator, &log, &options).unwrap();
+
+        // Boundaries at days 4, 6, ..., 18
+        assert_eq!(drift.comparisons.len(), 8);
+        assert_eq!(drift.comparisons[0].window_1.trace_indices.len(), 4);
+        assert_eq!(drift.change_points.len(), 1);
+        assert_eq!(
+            drift.change_points[0].boundary,
+            DateTime::UNIX_EPOCH.fixed_offset() + TimeDelta::days(10)
+        );
+    }
+}
@@ -0,0 +1,499 @@
+use std::{collections::HashMap, fs::File, io::Read, path::Path};
+
+use chrono::{DateTime, FixedOffset, LocalResult, NaiveDateTime, Offset, TimeZone, Utc};
+use process_mining::{
+    event_log::{Attribute, AttributeValue, Attributes, Event, Trace, XESEditableAttribute},
+    EventLog,
+};
+use thiserror::Error;
+
+use crate::utils::constants::{
+    ACTIVITY_KEY, LIFECYCLE_KEY, RESOURCE_KEY, START_TIMESTAMP_KEY, TIMESTAMP_KEY, TRACEID_KEY,
+};
+
+/// Formats tried (in order) to parse timestamps without an explicit format, after
+/// RFC 3339. Timestamps in these formats are interpreted in the configured timezone.
+const DEFAULT_NAIVE_TIMESTAMP_FORMATS: [&str; 4] = [
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y/%m/%d %H:%M:%S%.f",
+    "%d.%m.%Y %H:%M:%S%.f",
+];
+
+/// An event together with its (parsed) timestamp, used for sorting.
+type TimestampedEvent = (DateTime<FixedOffset>, Event);
+
+/// The mapping of CSV columns to event attributes.
+#[derive(Debug, Clone)]
+pub struct CsvColumnMapping {
+    /// The column containing the case id (`concept:name` of the trace).
+    pub case_id: String,
+    /// The column containing the activity (`concept:name`).
+    pub activity: String,
+    /// The column containing the (completion) timestamp (`time:timestamp`).
+    pub timestamp: String,
+    /// The column containing the start timestamp (`start_timestamp`).
+    pub start_timestamp: Option<String>,
+    /// The column containing the lifecycle transition (`lifecycle:transition`).
+    pub lifecycle: Option<String>,
+    /// The column containing the resource (`org:resource`).
+    pub resource: Option<String>,
+}
+
+impl CsvColumnMapping {
+    pub fn new(
+        case_id: impl Into<String>,
+        activity: impl Into<String>,
+        timestamp: impl Into<String>,
+    ) -> Self {
+        Self {
+            case_id: case_id.into(),
+            activity: activity.into(),
+            timestamp: timestamp.into(),
+            start_timestamp: None,
+            lifecycle: None,
+            resource: None,
+        }
+    }
+
+    pub fn with_start_timestamp(mut self, column: impl Into<String>) -> Self {
+        self.start_timestamp = Some(column.into());
+        self
+    }
+
+    pub fn with_lifecycle(mut self, column: impl Into<String>) -> Self {
+        self.lifecycle = Some(column.into());
+        self
+    }
+
+    pub fn with_resource(mut self, column: impl Into<String>) -> Self {
+        self.resource = Some(column.into());
+        self
+    }
+}
+
+/// Options for importing an event log from a CSV file.
+///
+/// Timestamps without an explicit offset are interpreted in the timezone `Tz`, e.g.,
+/// a `chrono_tz::Tz` to respect daylight saving time.
+#[derive(Debug, Clone)]
+pub struct CsvImportOptions<Tz: TimeZone = Utc> {
+    /// The mapping of columns to event attributes.
+    pub mapping: CsvColumnMapping,
+    /// The [`chrono` format](chrono::format::strftime) of the timestamps. If `None`,
+    /// RFC 3339 and a few common formats (e.g., `%Y-%m-%d %H:%M:%S`) are tried.
+    pub timestamp_format: Option<String>,
+    /// The timezone of timestamps without an explicit offset.
+    pub timezone: Tz,
+    /// The field delimiter.
+    pub delimiter: u8,
+    /// Whether to keep unmapped columns as String event attributes.
+    pub keep_other_columns: bool,
+}
+
+impl CsvImportOptions {
+    /// Options for a comma-separated file with timestamps in UTC. Unmapped columns
+    /// are kept as event attributes.
+    pub fn new(mapping: CsvColumnMapping) -> Self {
+        Self {
+            mapping,
+            timestamp_format: None,
+            timezone: Utc,
+            delimiter: b',',
+            keep_other_columns: true,
+        }
+    }
+}
+
+impl<Tz: TimeZone> CsvImportOptions<Tz> {
+    pub fn with_timestamp_format(mut self, timestamp_format: impl Into<String>) -> Self {
+        self.timestamp_format = Some(timestamp_format.into());
+        self
+    }
+
+    /// Interpret timestamps without an explicit offset in `timezone`.
+    pub fn with_timezone<Tz2: TimeZone>(self, timezone: Tz2) -> CsvImportOptions<Tz2> {
+        CsvImportOptions {
+            mapping: self.mapping,
+            timestamp_format: self.timestamp_format,
+            timezone,
+            delimiter: self.delimiter,
+            keep_other_columns: self.keep_other_columns,
+        }
+    }
+
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn with_other_columns(mut self, keep_other_columns: bool) -> Self {
+        self.keep_other_columns = keep_other_columns;
+        self
+    }
+
+    /// Parse a timestamp according to the configured format and timezone.
+    fn parse_timestamp(&self, value: &str) -> Result<DateTime<FixedOffset>, String> {
+        let value = value.trim();
+        match &self.timestamp_format {
+            Some(format) => match DateTime::parse_from_str(value, format) {
+                Ok(timestamp) => Ok(timestamp),
+                Err(_) => NaiveDateTime::parse_from_str(value, format)
+                    .map_err(|err| {
+                        format!("invalid timestamp \"{value}\" for format \"{format}\": {err}")
+                    })
+                    .and_then(|naive| self.localize(naive)),
+            },
+            None => DateTime::parse_from_rfc3339(value).or_else(|_| {
+                DEFAULT_NAIVE_TIMESTAMP_FORMATS
+                    .iter()
+                    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+                    .ok_or_else(|| format!("unrecognized timestamp format \"{value}\""))
+                    .and_then(|naive| self.localize(naive))
+            }),
+        }
+    }
+
+    /// Interpret a local time in the configured timezone. Local times repeated when
+    /// the clocks are turned back are resolved to the earlier time; local times
+    /// skipped when the clocks are turned forward are rejected.
+    fn localize(&self, naive: NaiveDateTime) -> Result<DateTime<FixedOffset>, String> {
+        let fixed = |timestamp: DateTime<Tz>| timestamp.with_timezone(&timestamp.offset().fix());
+        match self.timezone.from_local_datetime(&naive) {
+            LocalResult::Single(timestamp) => Ok(fixed(timestamp)),
+            LocalResult::Ambiguous(earlier, _) => Ok(fixed(earlier)),
+            LocalResult::None => Err(format!(
+                "local time \"{naive}\" does not exist in the timezone"
+            )),
+        }
+    }
+}
+
+/// An error in a single row of a CSV file.
+#[derive(Debug, Clone, Error)]
+#[error("line {line}{}: {message}", column.as_ref().map(|column| format!(", column \"{column}\"")).unwrap_or_default())]
+pub struct CsvRowError {
+    /// The line of the row in the file (1-based, the header is line 1).
+    pub line: u64,
+    /// The column of the invalid field, or `None` if the row itself is malformed
+    /// (e.g., has the wrong number of fields).
+    pub column: Option<String>,
+    pub message: String,
+}
+
+/// An error that can occur when importing an event log from a CSV file.
+#[derive(Debug, Error)]
+pub enum CsvImportError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Csv(#[from] ::csv::Error),
+    #[error("column \"{0}\" not found in the header")]
+    MissingColumn(String),
+    #[error("{} row(s) could not be parsed, the first error is at {}", .0.len(), .0[0])]
+    Rows(Vec<CsvRowError>),
+}
+
+/// Import an event log from a CSV file. See [`import_csv_reader`].
+pub fn import_csv_file<Tz: TimeZone>(
+    path: impl AsRef<Path>,
+    options: &CsvImportOptions<Tz>,
+) -> Result<EventLog, CsvImportError> {
+    import_csv_reader(File::open(path)?, options)
+}
+
+/// Import an event log from CSV data with a header row.
+///
+/// Each row is an event. The mapped columns are stored in the attributes named as
+/// in [`constants`](crate::utils::constants), and (if configured) all other columns
+/// as String attributes. Empty cells of optional columns are omitted. Events are
+/// grouped into traces by the case id, in the order of first appearance of each case,
+/// and sorted (stably) by their timestamp within each trace.
+///
+/// Returns a [`CsvImportError::Rows`] with _all_ rows that could not be parsed,
+/// e.g., due to an invalid timestamp, an empty case id or a wrong number of fields.
+pub fn import_csv_reader<R: Read, Tz: TimeZone>(
+    reader: R,
+    options: &CsvImportOptions<Tz>,
+) -> Result<EventLog, CsvImportError> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .from_reader(reader);
+    let headers = reader.headers()?.clone();
+    let column_index = |column: &str| {
+        headers
+            .iter()
+            .position(|header| header == column)
+            .ok_or_else(|| CsvImportError::MissingColumn(column.to_string()))
+    };
+
+    let mapping = &options.mapping;
+    let case_id_index = column_index(&mapping.case_id)?;
+    let activity_index = column_index(&mapping.activity)?;
+    let timestamp_index = column_index(&mapping.timestamp)?;
+    let optional_indices: Vec<(&str, usize)> = [
+        (START_TIMESTAMP_KEY, &mapping.start_timestamp),
+        (LIFECYCLE_KEY, &mapping.lifecycle),
+        (RESOURCE_KEY, &mapping.resource),
+    ]
+    .into_iter()
+    .filter_map(|(key, column)| column.as_ref().map(|column| (key, column)))
+    .map(|(key, column)| Ok((key, column_index(column)?)))
+    .collect::<Result<_, CsvImportError>>()?;
+
+    let mapped_indices: Vec<usize> = [case_id_index, activity_index, timestamp_index]
+        .into_iter()
+        .chain(optional_indices.iter().map(|(_, index)| *index))
+        .collect();
+    let other_indices: Vec<usize> = if options.keep_other_columns {
+        (0..headers.len())
+            .filter(|index| !mapped_indices.contains(index))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut case_indices: HashMap<String, usize> = HashMap::new();
+    let mut cases: Vec<(String, Vec<TimestampedEvent>)> = Vec::new();
+    let mut row_errors: Vec<CsvRowError> = Vec::new();
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(err) => match err.kind() {
+                ::csv::ErrorKind::UnequalLengths { pos, .. }
+                | ::csv::ErrorKind::Utf8 { pos, .. } => {
+                    row_errors.push(CsvRowError {
+                        line: pos.as_ref().map(|pos| pos.line()).unwrap_or_default(),
+                        column: None,
+                        message: err.to_string(),
+                    });
+                    continue;
+                }
+                _ => return Err(err.into()),
+            },
+        };
+        let line = record.position().map(|pos| pos.line()).unwrap_or_default();
+        let row_error = |index: usize, message: String| CsvRowError {
+            line,
+            column: Some(headers[index].to_string()),
+            message,
+        };
+        let field = |index: usize| record.get(index).unwrap_or_default();
+
+        let case_id = field(case_id_index);
+        if case_id.is_empty() {
+            row_errors.push(row_error(case_id_index, "empty case id".to_string()));
+            continue;
+        }
+        let activity = field(activity_index);
+        if activity.is_empty() {
+            row_errors.push(row_error(activity_index, "empty activity".to_string()));
+            continue;
+        }
+        let timestamp = match options.parse_timestamp(field(timestamp_index)) {
+            Ok(timestamp) => timestamp,
+            Err(message) => {
+                row_errors.push(row_error(timestamp_index, message));
+                continue;
+            }
+        };
+
+        let mut attributes = Attributes::new();
+        attributes.add_attribute(Attribute::new(
+            ACTIVITY_KEY.to_string(),
+            AttributeValue::String(activity.to_string()),
+        ));
+        attributes.add_attribute(Attribute::new(
+            TIMESTAMP_KEY.to_string(),
+            AttributeValue::Date(timestamp),
+        ));
+
+        let mut row_valid = true;
+        for (key, index) in optional_indices.iter() {
+            let value = field(*index);
+            if value.is_empty() {
+                continue;
+            }
+            let value = if *key == START_TIMESTAMP_KEY {
+                match options.parse_timestamp(value) {
+                    Ok(start_timestamp) => AttributeValue::Date(start_timestamp),
+                    Err(message) => {
+                        row_errors.push(row_error(*index, message));
+                        row_valid = false;
+                        break;
+                    }
+                }
+            } else {
+                AttributeValue::String(value.to_string())
+            };
+            attributes.add_attribute(Attribute::new(key.to_string(), value));
+        }
+        if !row_valid {
+            continue;
+        }
+
+        other_indices.iter().for_each(|index| {
+            let value = field(*index);
+            if !value.is_empty() {
+                attributes.add_attribute(Attribute::new(
+                    headers[*index].to_string(),
+                    AttributeValue::String(value.to_string()),
+                ));
+            }
+        });
+
+        let case_index = *case_indices.entry(case_id.to_string()).or_insert_with(|| {
+            cases.push((case_id.to_string(), Vec::new()));
+            cases.len() - 1
+        });
+        cases[case_index].1.push((timestamp, Event { attributes }));
+    }
+
+    if !row_errors.is_empty() {
+        return Err(CsvImportError::Rows(row_errors));
+    }
+
+    let traces = cases
+        .into_iter()
+        .map(|(case_id, mut events)| {
+            events.sort_by_key(|(timestamp, _)| *timestamp);
+
+            let mut attributes = Attributes::new();
+            attributes.add_attribute(Attribute::new(
+                TRACEID_KEY.to_string(),
+                AttributeValue::String(case_id),
+            ));
+            Trace {
+                attributes,
+                events: events.into_iter().map(|(_, event)| event).collect(),
+            }
+        })
+        .collect();
+
+    Ok(EventLog {
+        attributes: Attributes::new(),
+        traces,
+        extensions: None,
+        classifiers: None,
+        global_trace_attrs: None,
+        global_event_attrs: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comparators::common::extraction::project_traces_on_activity,
+        utils::attributes::{get_resource, get_service_time, HasAttributes},
+    };
+
+    fn options() -> CsvImportOptions<FixedOffset> {
+        CsvImportOptions::new(
+            CsvColumnMapping::new("case", "activity", "end")
+                .with_start_timestamp("start")
+                .with_resource("resource"),
+        )
+        .with_timezone(FixedOffset::east_opt(3600).unwrap())
+    }
+
+    #[test]
+    fn test_import_csv() {
+        let data = "\
+case,activity,start,end,resource,channel
+1,b,2024-01-01 10:30:00,2024-01-01 11:00:00,bob,web
+2,a,,2024-01-02T09:00:00+00:00,alice,
+1,a,2024-01-01 10:00:00,2024-01-01 10:15:00,alice,web
+";
+        let log = import_csv_reader(data.as_bytes(), &options()).unwrap();
+
+        assert_eq!(
+            project_traces_on_activity(&log).unwrap(),
+            vec![vec!["a", "b"], vec!["a"]]
+        );
+        assert_eq!(
+            log.traces[0].get_string_by_key(TRACEID_KEY).unwrap(),
+            "1".to_string()
+        );
+
+        let first_event = &log.traces[0].events[0];
+        assert_eq!(get_resource(first_event).unwrap(), "alice");
+        assert_eq!(get_service_time(first_event).unwrap().num_minutes(), 15);
+        assert_eq!(first_event.get_string_by_key("channel").unwrap(), "web");
+        assert_eq!(
+            first_event
+                .get_time_by_key(TIMESTAMP_KEY)
+                .unwrap()
+                .to_rfc3339(),
+            "2024-01-01T10:15:00+01:00"
+        );
+
+        // Empty optional cells are omitted
+        let second_case_event = &log.traces[1].events[0];
+        assert!(second_case_event
+            .get_attribute_by_key(START_TIMESTAMP_KEY)
+            .is_err());
+        assert!(second_case_event.get_attribute_by_key("channel").is_err());
+    }
+
+    #[test]
+    fn test_import_csv_row_errors() {
+        let data = "\
+case,activity,start,end,resource
+1,a,2024-01-01 10:00:00,yesterday,alice
+,a,2024-01-01 10:00:00,2024-01-01 10:15:00,alice
+3,a,2024-01-01 10:00:00
+2,a,2024-01-01 10:00:00,2024-01-01 10:15:00,alice
+";
+        match import_csv_reader(data.as_bytes(), &options()) {
+            Err(CsvImportError::Rows(errors)) => {
+                assert_eq!(
+                    errors
+                        .iter()
+                        .map(|err| (err.line, err.column.as_deref()))
+                        .collect::<Vec<_>>(),
+                    vec![(2, Some("end")), (3, Some("case")), (4, None)]
+                );
+            }
+            other => panic!("Expected row errors, got {other:?}"),
+        }
+
+        assert!(matches!(
+            import_csv_reader(
+                data.as_bytes(),
+                &CsvImportOptions::new(CsvColumnMapping::new("case", "activity", "timestamp"))
+            ),
+            Err(CsvImportError::MissingColumn(column)) if column == "timestamp"
+        ));
+    }
+
+    #[test]
+    fn test_import_csv_dst_timezone() {
+        let options = CsvImportOptions::new(CsvColumnMapping::new("case", "activity", "end"))
+            .with_timezone(chrono_tz::Europe::Berlin);
+
+        let data = "\
+case,activity,end
+1,a,2024-03-30 12:00:00
+1,b,2024-03-31 12:00:00
+";
+        let log = import_csv_reader(data.as_bytes(), &options).unwrap();
+        let offsets: Vec<i32> = log.traces[0]
+            .events
+            .iter()
+            .map(|event| {
+                let timestamp = event.get_time_by_key(TIMESTAMP_KEY).unwrap();
+                timestamp.offset().local_minus_utc()
+            })
+            .collect();
+        assert_eq!(offsets, vec![3600, 7200]);
+
+        // 02:30 is skipped when switching to daylight saving time
+        let skipped = "case,activity,end\n1,a,2024-03-31 02:30:00\n";
+        match import_csv_reader(skipped.as_bytes(), &options) {
+            Err(CsvImportError::Rows(errors)) => assert_eq!(errors[0].line, 2),
+            other => panic!("Expected row errors, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,3 @@
+//! Import of event logs from formats other than XES.
+
+pub mod csv;
@@ -7,5 +7,12 @@
 pub mod binning;
 pub mod comparators;
 pub mod distance;
+pub mod drift;
 pub mod emd;
+pub mod input;
+pub mod ocel;
+pub mod pairwise;
+pub mod report;
+pub mod representation;
+pub mod stats;
 pub mod utils;
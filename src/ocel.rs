@@ -0,0 +1,362 @@
+//! Object-centric comparisons of OCEL 2.0 event logs.
+//!
+//! An [`OCEL`] is flattened per object type into an [`EventLog`] (see [`flatten_ocel`]),
+//! on which the existing comparators are run. The results are grouped per object type.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Debug,
+    hash::Hash,
+};
+
+use process_mining::{
+    event_log::{Attribute, AttributeValue, Attributes, Event, Trace, XESEditableAttribute},
+    ocel::ocel_struct::{OCELAttributeValue, OCELEvent},
+    EventLog, OCEL,
+};
+
+use crate::{
+    comparators::{
+        bootstrap::bootstrap_comparator::{BootstrapTestComparator, BootstrapTestComparisonResult},
+        permutation_test::permutation_test_comparator::{
+            PermutationTestComparator, PermutationTestComparisonResult,
+        },
+    },
+    utils::{
+        attributes::attribute_error::AttributeResult,
+        constants::{ACTIVITY_KEY, TIMESTAMP_KEY, TRACEID_KEY},
+    },
+};
+
+/// The object types of an [`OCEL`] that have at least one object, in sorted order.
+pub fn object_types(ocel: &OCEL) -> Vec<String> {
+    ocel.objects
+        .iter()
+        .map(|object| object.object_type.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Flatten an [`OCEL`] on an object type.
+///
+/// Each object of the type becomes a [`Trace`] (with the object id as `concept:name`)
+/// containing all events related to the object, sorted by time. Events have the
+/// event type as `concept:name`, the event time as `time:timestamp`, and all their
+/// (non-null) OCEL attributes. Objects without any related events are omitted.
+///
+/// Note that events related to multiple objects of the type are duplicated (the
+/// well-known _convergence_ problem of flattening).
+pub fn flatten_ocel(ocel: &OCEL, object_type: &str) -> EventLog {
+    let mut events_per_object: HashMap<&str, Vec<&OCELEvent>> = ocel
+        .objects
+        .iter()
+        .filter(|object| object.object_type == object_type)
+        .map(|object| (object.id.as_str(), Vec::new()))
+        .collect();
+
+    ocel.events.iter().for_each(|event| {
+        // An event can relate to the same object multiple times (with different
+        // qualifiers), but should only appear once in the object's trace
+        event
+            .relationships
+            .iter()
+            .map(|relationship| relationship.object_id.as_str())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .for_each(|object_id| {
+                if let Some(events) = events_per_object.get_mut(object_id) {
+                    events.push(event);
+                }
+            })
+    });
+
+    let traces = ocel
+        .objects
+        .iter()
+        .filter(|object| object.object_type == object_type)
+        .filter_map(|object| {
+            let mut events = events_per_object.remove(object.id.as_str())?;
+            if events.is_empty() {
+                return None;
+            }
+            events.sort_by_key(|event| event.time);
+
+            let mut attributes = Attributes::new();
+            attributes.add_attribute(Attribute::new(
+                TRACEID_KEY.to_string(),
+                AttributeValue::String(object.id.clone()),
+            ));
+            Some(Trace {
+                attributes,
+                events: events.into_iter().map(ocel_event_to_event).collect(),
+            })
+        })
+        .collect();
+
+    EventLog {
+        attributes: Attributes::new(),
+        traces,
+        extensions: None,
+        classifiers: None,
+        global_trace_attrs: None,
+        global_event_attrs: None,
+    }
+}
+
+/// Flatten an [`OCEL`] on each of its object types. See [`flatten_ocel`].
+pub fn flatten_ocel_per_object_type(ocel: &OCEL) -> BTreeMap<String, EventLog> {
+    object_types(ocel)
+        .into_iter()
+        .map(|object_type| {
+            let log = flatten_ocel(ocel, &object_type);
+            (object_type, log)
+        })
+        .collect()
+}
+
+fn ocel_event_to_event(event: &OCELEvent) -> Event {
+    let mut attributes = Attributes::new();
+    attributes.add_attribute(Attribute::new(
+        ACTIVITY_KEY.to_string(),
+        AttributeValue::String(event.event_type.clone()),
+    ));
+    attributes.add_attribute(Attribute::new(
+        TIMESTAMP_KEY.to_string(),
+        AttributeValue::Date(event.time),
+    ));
+    event
+        .attributes
+        .iter()
+        .filter(|attribute| attribute.name != ACTIVITY_KEY && attribute.name != TIMESTAMP_KEY)
+        .for_each(|attribute| {
+            let value = match &attribute.value {
+                OCELAttributeValue::Time(value) => AttributeValue::Date(*value),
+                OCELAttributeValue::Integer(value) => AttributeValue::Int(*value),
+                OCELAttributeValue::Float(value) => AttributeValue::Float(*value),
+                OCELAttributeValue::Boolean(value) => AttributeValue::Boolean(*value),
+                OCELAttributeValue::String(value) => AttributeValue::String(value.clone()),
+                OCELAttributeValue::Null => return,
+            };
+            attributes.add_attribute(Attribute::new(attribute.name.clone(), value));
+        });
+    Event { attributes }
+}
+
+/// The object types to compare: The given ones, or the object types present in
+/// both OCELs.
+fn object_types_to_compare(
+    ocel_1: &OCEL,
+    ocel_2: &OCEL,
+    object_types_filter: Option<&[String]>,
+) -> Vec<String> {
+    match object_types_filter {
+        Some(filter) => filter.to_vec(),
+        None => {
+            let types_2: BTreeSet<String> = object_types(ocel_2).into_iter().collect();
+            object_types(ocel_1)
+                .into_iter()
+                .filter(|object_type| types_2.contains(object_type))
+                .collect()
+        }
+    }
+}
+
+/// The flattened event logs of both OCELs for each object type to compare. Object
+/// types for which either flattened event log has no traces (e.g., object types of
+/// the filter missing in an OCEL) are skipped, since there is nothing to compare.
+fn flattened_logs_to_compare(
+    ocel_1: &OCEL,
+    ocel_2: &OCEL,
+    object_types_filter: Option<&[String]>,
+) -> Vec<(String, EventLog, EventLog)> {
+    object_types_to_compare(ocel_1, ocel_2, object_types_filter)
+        .into_iter()
+        .map(|object_type| {
+            let log_1 = flatten_ocel(ocel_1, &object_type);
+            let log_2 = flatten_ocel(ocel_2, &object_type);
+            (object_type, log_1, log_2)
+        })
+        .filter(|(_, log_1, log_2)| !log_1.traces.is_empty() && !log_2.traces.is_empty())
+        .collect()
+}
+
+/// Compare two OCELs per object type using a [`PermutationTestComparator`].
+///
+/// Both OCELs are flattened on each object type (see [`flatten_ocel`]), and the
+/// flattened event logs are compared. If `object_types` is `None`, all object types
+/// present in both OCELs are compared. Object types without any (related) objects
+/// in either OCEL are skipped and not part of the results.
+///
+/// Returns an `Err` if required attributes are missing in any flattened event log.
+/// Timed comparators require a `start_timestamp`, which OCEL events do not have; in
+/// this case, flatten the OCELs manually and see [`ensure_start_timestamp_key`].
+///
+/// [`ensure_start_timestamp_key`]: crate::comparators::common::preparation::ensure_start_timestamp_key
+pub fn compare_ocel_permutation<T, C>(
+    comparator: &C,
+    ocel_1: &OCEL,
+    ocel_2: &OCEL,
+    object_types: Option<&[String]>,
+    distribution_size: usize,
+    seed: Option<u64>,
+) -> AttributeResult<BTreeMap<String, PermutationTestComparisonResult>>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: PermutationTestComparator<T>,
+{
+    flattened_logs_to_compare(ocel_1, ocel_2, object_types)
+        .into_iter()
+        .map(|(object_type, log_1, log_2)| {
+            let result = comparator.compare(&log_1, &log_2, distribution_size, seed)?;
+            Ok((object_type, result))
+        })
+        .collect()
+}
+
+/// Compare two OCELs per object type using a [`BootstrapTestComparator`].
+///
+/// Both OCELs are flattened on each object type (see [`flatten_ocel`]), and the
+/// flattened event logs are compared. If `object_types` is `None`, all object types
+/// present in both OCELs are compared. Object types without any (related) objects
+/// in either OCEL are skipped and not part of the results.
+///
+/// Returns an `Err` if required attributes are missing in any flattened event log.
+/// Timed comparators require a `start_timestamp`, which OCEL events do not have; in
+/// this case, flatten the OCELs manually and see [`ensure_start_timestamp_key`].
+///
+/// [`ensure_start_timestamp_key`]: crate::comparators::common::preparation::ensure_start_timestamp_key
+pub fn compare_ocel_bootstrap<T, C>(
+    comparator: &C,
+    ocel_1: &OCEL,
+    ocel_2: &OCEL,
+    object_types: Option<&[String]>,
+    resample_size: usize,
+    distribution_size: usize,
+    seed: Option<u64>,
+) -> AttributeResult<BTreeMap<String, BootstrapTestComparisonResult>>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: BootstrapTestComparator<T>,
+{
+    flattened_logs_to_compare(ocel_1, ocel_2, object_types)
+        .into_iter()
+        .map(|(object_type, log_1, log_2)| {
+            let result =
+                comparator.compare(&log_1, &log_2, resample_size, distribution_size, seed)?;
+            Ok((object_type, result))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comparators::{
+        common::extraction::project_traces_on_activity,
+        permutation_test::control_flow::ControlFlowPermutationComparator,
+    };
+    use chrono::{DateTime, TimeDelta};
+    use process_mining::ocel::ocel_struct::{OCELObject, OCELRelationship};
+
+    fn event(id: &str, event_type: &str, hour: i64, objects: &[&str]) -> OCELEvent {
+        OCELEvent {
+            id: id.to_string(),
+            event_type: event_type.to_string(),
+            time: DateTime::UNIX_EPOCH.fixed_offset() + TimeDelta::hours(hour),
+            attributes: Vec::new(),
+            relationships: objects
+                .iter()
+                .map(|object_id| OCELRelationship {
+                    object_id: object_id.to_string(),
+                    qualifier: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    fn object(id: &str, object_type: &str) -> OCELObject {
+        OCELObject {
+            id: id.to_string(),
+            object_type: object_type.to_string(),
+            attributes: Vec::new(),
+            relationships: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_flatten_ocel() {
+        let ocel = OCEL {
+            event_types: Vec::new(),
+            object_types: Vec::new(),
+            events: vec![
+                event("e2", "pick item", 2, &["o1", "i1"]),
+                event("e1", "place order", 1, &["o1", "i1", "i2"]),
+                event("e3", "pick item", 3, &["o1", "i2"]),
+                event("e4", "send package", 4, &["o1"]),
+            ],
+            objects: vec![
+                object("o1", "order"),
+                object("i1", "item"),
+                object("i2", "item"),
+                object("i3", "item"),
+            ],
+        };
+
+        assert_eq!(object_types(&ocel), vec!["item", "order"]);
+
+        let orders = flatten_ocel(&ocel, "order");
+        assert_eq!(
+            project_traces_on_activity(&orders).unwrap(),
+            vec![vec![
+                "place order",
+                "pick item",
+                "pick item",
+                "send package"
+            ]]
+        );
+
+        // i3 has no events and is omitted
+        let items = flatten_ocel(&ocel, "item");
+        assert_eq!(
+            project_traces_on_activity(&items).unwrap(),
+            vec![
+                vec!["place order", "pick item"],
+                vec!["place order", "pick item"]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_skips_missing_object_types() {
+        let ocel = |events: Vec<OCELEvent>, objects: Vec<OCELObject>| OCEL {
+            event_types: Vec::new(),
+            object_types: Vec::new(),
+            events,
+            objects,
+        };
+        let ocel_1 = ocel(
+            vec![
+                event("e1", "place order", 1, &["o1", "i1"]),
+                event("e2", "pick item", 2, &["i1"]),
+            ],
+            vec![object("o1", "order"), object("i1", "item")],
+        );
+        let ocel_2 = ocel(
+            vec![event("e1", "place order", 1, &["o1"])],
+            vec![object("o1", "order")],
+        );
+
+        let filter = vec!["item".to_string(), "order".to_string()];
+        let results = compare_ocel_permutation(
+            &ControlFlowPermutationComparator,
+            &ocel_1,
+            &ocel_2,
+            Some(&filter),
+            10,
+            Some(0),
+        )
+        .unwrap();
+        assert_eq!(results.keys().collect::<Vec<_>>(), vec!["order"]);
+    }
+}
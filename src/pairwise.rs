@@ -0,0 +1,351 @@
+//! Pairwise EMDs between many event logs, e.g., to cluster business units by their
+//! behavior.
+//!
+//! The representations of all event logs are extracted at once and the distances
+//! between all variants are computed only once; Each pair of event logs then only
+//! requires solving an EMD on the corresponding part of the distance matrix.
+
+use std::{
+    fmt::Debug,
+    fs::File,
+    hash::Hash,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use itertools::Itertools;
+use ndarray::Array2;
+use process_mining::{event_log::XESEditableAttribute, EventLog};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    comparators::{
+        common::{
+            comparison_error::ComparisonError, preparation::log_with_traces,
+            result::ResultExportError, stochastic_language::StochasticLanguage,
+        },
+        permutation_test::permutation_test_comparator::{
+            PermutationTestComparator, VariantDistances,
+        },
+    },
+    distance::matrix::DistanceMatrix,
+    emd::compute_emd,
+    utils::{constants::LOG_NAME_KEY, nullable_float},
+};
+
+/// A symmetric matrix of distances between labeled event logs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    into = "SerializedDistanceMatrix",
+    try_from = "SerializedDistanceMatrix"
+)]
+pub struct LabeledDistanceMatrix {
+    /// The labels of the event logs, in the order of the rows/columns of `distances`.
+    pub labels: Vec<String>,
+    /// The distances between the event logs.
+    pub distances: Array2<f64>,
+}
+
+impl LabeledDistanceMatrix {
+    /// Replace the labels of the event logs. Returns `None` if the number of labels
+    /// does not match the number of event logs.
+    pub fn with_labels(mut self, labels: Vec<String>) -> Option<Self> {
+        if labels.len() != self.labels.len() {
+            return None;
+        }
+        self.labels = labels;
+        Some(self)
+    }
+
+    /// The distance between the event logs labeled `label_1` and `label_2`.
+    pub fn get(&self, label_1: &str, label_2: &str) -> Option<f64> {
+        let index = |label| self.labels.iter().position(|l| l == label);
+        Some(self.distances[(index(label_1)?, index(label_2)?)])
+    }
+
+    /// The upper triangle of the distance matrix (without the diagonal) in row-major
+    /// order, i.e., the condensed distance matrix expected by hierarchical clustering
+    /// implementations such as `scipy.cluster.hierarchy.linkage`.
+    pub fn condensed(&self) -> Vec<f64> {
+        (0..self.labels.len())
+            .tuple_combinations()
+            .map(|(i, j)| self.distances[(i, j)])
+            .collect()
+    }
+
+    /// Serialize the matrix as JSON, with the labels and the distances as nested
+    /// arrays (rows).
+    pub fn to_json(&self) -> Result<String, ResultExportError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Deserialize a matrix from a JSON string created with [`to_json`](LabeledDistanceMatrix::to_json).
+    pub fn from_json(json: &str) -> Result<Self, ResultExportError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Write the matrix as JSON to a file.
+    pub fn write_json(&self, path: impl AsRef<Path>) -> Result<(), ResultExportError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the square matrix as CSV, with a header row and a first column containing
+    /// the labels.
+    pub fn write_csv(&self, writer: impl Write) -> Result<(), ResultExportError> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(std::iter::once("").chain(self.labels.iter().map(String::as_str)))?;
+        for (label, row) in self.labels.iter().zip(self.distances.rows()) {
+            writer.write_record(
+                std::iter::once(label.clone()).chain(row.iter().map(|d| d.to_string())),
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// The serialized form of a [`LabeledDistanceMatrix`].
+#[derive(Serialize, Deserialize)]
+struct SerializedDistanceMatrix {
+    labels: Vec<String>,
+    #[serde(deserialize_with = "nullable_float::deserialize_matrix")]
+    distances: Vec<Vec<f64>>,
+}
+
+impl From<LabeledDistanceMatrix> for SerializedDistanceMatrix {
+    fn from(matrix: LabeledDistanceMatrix) -> Self {
+        Self {
+            labels: matrix.labels,
+            distances: matrix
+                .distances
+                .rows()
+                .into_iter()
+                .map(|row| row.to_vec())
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<SerializedDistanceMatrix> for LabeledDistanceMatrix {
+    type Error = String;
+
+    fn try_from(matrix: SerializedDistanceMatrix) -> Result<Self, Self::Error> {
+        let n = matrix.labels.len();
+        if matrix.distances.len() != n || matrix.distances.iter().any(|row| row.len() != n) {
+            return Err(format!("Expected a {n}x{n} distance matrix"));
+        }
+        let distances = Array2::from_shape_vec((n, n), matrix.distances.concat())
+            .map_err(|err| err.to_string())?;
+        Ok(Self {
+            labels: matrix.labels,
+            distances,
+        })
+    }
+}
+
+/// Compute the EMD between each pair of `logs`.
+///
+/// The event logs are labeled by their `concept:name` attribute if present, and
+/// `log_<index>` otherwise (see [`LabeledDistanceMatrix::with_labels`]).
+///
+/// Returns an `Err` if the comparator fails to extract the representations (see
+/// [`PermutationTestComparator::compare`]), if it does not extract one representation
+/// per trace, or if an event log has no traces.
+pub fn pairwise_emds<T, C>(
+    comparator: &C,
+    logs: &[&EventLog],
+) -> Result<LabeledDistanceMatrix, ComparisonError>
+where
+    T: Hash + Eq + Clone + Ord + Debug,
+    C: PermutationTestComparator<T> + ?Sized,
+{
+    let labels = logs
+        .iter()
+        .enumerate()
+        .map(|(index, log)| {
+            log.attributes
+                .get_by_key(LOG_NAME_KEY)
+                .and_then(|attr| attr.value.try_as_string().cloned())
+                .unwrap_or_else(|| format!("log_{index}"))
+        })
+        .collect();
+
+    if logs.is_empty() {
+        return Ok(LabeledDistanceMatrix {
+            labels,
+            distances: Array2::zeros((0, 0)),
+        });
+    }
+
+    let PooledVariants {
+        variant_distances,
+        population,
+        log_sizes,
+    } = pool_variants(comparator, logs)?;
+    let languages = split_languages(&population, &log_sizes);
+
+    Ok(LabeledDistanceMatrix {
+        labels,
+        distances: pairwise_language_emds(variant_distances.distances.as_ref(), &languages),
+    })
+}
+
+/// The representations of several event logs, extracted at once, as indices into
+/// the distances between all their variants.
+pub(crate) struct PooledVariants<'a, T> {
+    pub(crate) variant_distances: VariantDistances<'a, T>,
+    /// The variant index of each case of all event logs, in order.
+    pub(crate) population: Vec<usize>,
+    /// The number of cases of each event log.
+    pub(crate) log_sizes: Vec<usize>,
+}
+
+/// Extract the representations of the pooled `logs` (so that, e.g., the binning of
+/// service times is consistent across the event logs) and compute the distances
+/// between all variants.
+///
+/// Returns an `Err` if an event log has no traces, as the EMD to its (empty)
+/// stochastic language is undefined, or if the comparator does not extract one
+/// representation per trace, as they could not be assigned to the event logs.
+pub(crate) fn pool_variants<'a, T, C>(
+    comparator: &'a C,
+    logs: &[&EventLog],
+) -> Result<PooledVariants<'a, T>, ComparisonError>
+where
+    T: Hash + Eq + Clone + Ord + Debug + 'a,
+    C: PermutationTestComparator<T> + ?Sized,
+{
+    if let Some(index) = logs.iter().position(|log| log.traces.is_empty()) {
+        return Err(ComparisonError::EmptyLog(index));
+    }
+    let log_sizes: Vec<usize> = logs.iter().map(|log| log.traces.len()).collect();
+    let pooled_log = log_with_traces(
+        logs[0],
+        logs.iter()
+            .flat_map(|log| log.traces.iter().cloned())
+            .collect(),
+    );
+    let (pooled_behavior, _) =
+        comparator.extract_representations(&pooled_log, &log_with_traces(logs[0], Vec::new()))?;
+    ComparisonError::check_representation_count(log_sizes.iter().sum(), pooled_behavior.len())?;
+    let variant_distances = comparator.compute_variant_distances(&pooled_behavior, &[]);
+
+    let population = pooled_behavior
+        .iter()
+        .map(|item| variant_distances.variants.binary_search(item).unwrap())
+        .collect();
+    Ok(PooledVariants {
+        variant_distances,
+        population,
+        log_sizes,
+    })
+}
+
+/// Split a population of variant indices into consecutive groups of the given sizes.
+pub(crate) fn split_languages(
+    population: &[usize],
+    sizes: &[usize],
+) -> Vec<StochasticLanguage<usize>> {
+    let mut offset = 0;
+    sizes
+        .iter()
+        .map(|size| {
+            let language =
+                StochasticLanguage::from_items(population[offset..offset + size].to_vec());
+            offset += size;
+            language
+        })
+        .collect()
+}
+
+/// The EMD between two stochastic languages over variant indices into `dists`.
+pub(crate) fn language_emd(
+    dists: &dyn DistanceMatrix,
+    lang_1: &StochasticLanguage<usize>,
+    lang_2: &StochasticLanguage<usize>,
+) -> f64 {
+    let projected_dists = dists.select(&lang_1.variants, &lang_2.variants);
+    compute_emd(
+        lang_1.frequencies.clone(),
+        lang_2.frequencies.clone(),
+        &projected_dists,
+    )
+    .emd
+}
+
+/// The symmetric matrix of EMDs between each pair of `languages`.
+pub(crate) fn pairwise_language_emds(
+    dists: &dyn DistanceMatrix,
+    languages: &[StochasticLanguage<usize>],
+) -> Array2<f64> {
+    let n = languages.len();
+    let mut emds = Array2::zeros((n, n));
+    (0..n).tuple_combinations().for_each(|(i, j)| {
+        emds[(i, j)] = language_emd(dists, &languages[i], &languages[j]);
+        emds[(j, i)] = emds[(i, j)];
+    });
+    emds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comparators::{
+        common::approximation::{ApproximateComparator, Approximation},
+        permutation_test::control_flow::ControlFlowPermutationComparator,
+    };
+    use process_mining_macros::event_log;
+
+    #[test]
+    fn test_pairwise_emds() {
+        let log_1 = event_log!([a, b], [a, b]; base_timestamp=EPOCH);
+        let log_2 = event_log!([a, b], [a, c]; base_timestamp=EPOCH);
+        let log_3 = event_log!([a, b], [a, b]; base_timestamp=EPOCH);
+        let matrix =
+            pairwise_emds(&ControlFlowPermutationComparator, &[&log_1, &log_2, &log_3]).unwrap();
+
+        assert_eq!(matrix.labels, vec!["log_0", "log_1", "log_2"]);
+        assert_eq!(matrix.distances.shape(), &[3, 3]);
+        // <a,b> and <a,c> have a postnormalized Levenshtein distance of 0.5
+        assert_eq!(matrix.condensed(), vec![0.25, 0.0, 0.25]);
+        assert_eq!(matrix.get("log_1", "log_0"), Some(0.25));
+
+        let json = matrix.to_json().unwrap();
+        assert_eq!(LabeledDistanceMatrix::from_json(&json).unwrap(), matrix);
+
+        let mut csv = Vec::new();
+        matrix.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            ",log_0,log_1,log_2\nlog_0,0,0.25,0\nlog_1,0.25,0,0.25\nlog_2,0,0.25,0\n"
+        );
+    }
+
+    #[test]
+    fn test_pairwise_emds_errors() {
+        let log_1 = event_log!([a, b], [a, c]; base_timestamp=EPOCH);
+        let log_2 = event_log!([a, b]; base_timestamp=EPOCH);
+        let mut empty_log = log_2.clone();
+        empty_log.traces.clear();
+        assert!(matches!(
+            pairwise_emds(&ControlFlowPermutationComparator, &[&log_1, &empty_log]),
+            Err(ComparisonError::EmptyLog(1))
+        ));
+
+        // The subsampled representations cannot be assigned to the event logs
+        let subsampling = ApproximateComparator::new(
+            ControlFlowPermutationComparator,
+            Approximation::Subsample { max_traces: 1 },
+        );
+        assert!(matches!(
+            pairwise_emds(&subsampling, &[&log_1, &log_2]),
+            Err(ComparisonError::RepresentationCount {
+                expected: 3,
+                found: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,414 @@
+//! Self-contained HTML reports of comparison results.
+//!
+//! The report contains a histogram (inline SVG) of the test distribution with the
+//! observed EMD marked, the p-value, the run parameters and, if the stochastic languages
+//! of the two event logs are given, the variants whose frequencies differ the most.
+//! It does not load any external resources.
+
+use std::{collections::HashMap, fmt::Debug, fmt::Write, hash::Hash, path::Path};
+
+use itertools::Itertools;
+
+use crate::comparators::common::{
+    result::ComparisonResult, stochastic_language::StochasticLanguage,
+};
+
+const SVG_WIDTH: f64 = 720.0;
+const SVG_HEIGHT: f64 = 300.0;
+const SVG_MARGIN: f64 = 40.0;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em auto;max-width:60em;color:#222}\
+table{border-collapse:collapse;margin:1em 0}\
+td,th{border:1px solid #ccc;padding:.3em .6em;text-align:left}\
+th{background:#f3f3f3}\
+td.num{text-align:right;font-family:monospace}\
+code{word-break:break-all}";
+
+/// A report of a [`ComparisonResult`], rendered as a standalone HTML document.
+///
+/// ```ignore
+/// let report = ComparisonReport::new(&result)
+///     .with_title("Before vs. after the redesign")
+///     .with_languages(&stoch_lang_1, &stoch_lang_2);
+/// report.write("report.html")?;
+/// ```
+#[derive(Debug)]
+pub struct ComparisonReport<'a, R, T = ()>
+where
+    T: Hash + Eq + Clone,
+{
+    result: &'a R,
+    languages: Option<(&'a StochasticLanguage<T>, &'a StochasticLanguage<T>)>,
+    title: String,
+    num_bins: usize,
+    top_variants: usize,
+}
+
+impl<'a, R: ComparisonResult> ComparisonReport<'a, R> {
+    /// Create a report for the result with 30 histogram bins and the 10 most differing
+    /// variants.
+    pub fn new(result: &'a R) -> Self {
+        Self {
+            result,
+            languages: None,
+            title: "Process Hypothesis Test".to_string(),
+            num_bins: 30,
+            top_variants: 10,
+        }
+    }
+}
+
+impl<'a, R, T> ComparisonReport<'a, R, T>
+where
+    R: ComparisonResult,
+    T: Hash + Eq + Clone + Debug,
+{
+    /// Include the variants with the largest difference in relative frequency between
+    /// the two stochastic languages.
+    pub fn with_languages<U: Hash + Eq + Clone + Debug>(
+        self,
+        language_1: &'a StochasticLanguage<U>,
+        language_2: &'a StochasticLanguage<U>,
+    ) -> ComparisonReport<'a, R, U> {
+        ComparisonReport {
+            result: self.result,
+            languages: Some((language_1, language_2)),
+            title: self.title,
+            num_bins: self.num_bins,
+            top_variants: self.top_variants,
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// The number of bins of the histogram of the test distribution.
+    pub fn with_bins(mut self, num_bins: usize) -> Self {
+        self.num_bins = num_bins.max(1);
+        self
+    }
+
+    /// The number of variants to list in the table of differing variants.
+    pub fn with_top_variants(mut self, top_variants: usize) -> Self {
+        self.top_variants = top_variants;
+        self
+    }
+
+    /// Render the report as an HTML document.
+    pub fn render(&self) -> String {
+        let metadata = self.result.metadata();
+        let mut html = String::new();
+
+        // Writing to a `String` cannot fail.
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n\
+             <h1>{title}</h1>\n",
+            title = escape(&self.title),
+        );
+
+        let estimate = self.result.pvalue_estimate();
+        let _ = write!(
+            html,
+            "<h2>Result</h2>\n<table>\n\
+             <tr><th>p-value ((b+1)/(m+1))</th><td class=\"num\">{:.6}</td></tr>\n\
+             <tr><th>Raw p-value (b/m)</th><td class=\"num\">{:.6}</td></tr>\n\
+             <tr><th>{:.0}% confidence interval</th><td class=\"num\">[{:.6}, {:.6}]</td></tr>\n\
+             <tr><th>Standard error</th><td class=\"num\">{:.6}</td></tr>\n\
+             <tr><th>Observed EMD</th><td class=\"num\">{}</td></tr>\n\
+             <tr><th>Distribution size</th><td class=\"num\">{}</td></tr>\n\
+             </table>\n",
+            self.result.pvalue(),
+            estimate.raw,
+            estimate.confidence * 100.0,
+            estimate.confidence_interval.0,
+            estimate.confidence_interval.1,
+            estimate.standard_error,
+            self.result.logs_emd(),
+            self.result.distribution().len(),
+        );
+        if let Some(summary) = self.result.sequential() {
+            let _ = writeln!(
+                html,
+                "<p>Sequential test: {} of at most {} samples used, decision at alpha = {}: \
+                 {:?}.</p>",
+                summary.samples_used, summary.max_samples, summary.alpha, summary.decision,
+            );
+        }
+
+        let effect_size = self.result.effect_size();
+        let _ = write!(
+            html,
+            "<h2>Effect Size</h2>\n<table>\n\
+             <tr><th>z-score</th><td class=\"num\">{}</td></tr>\n\
+             <tr><th>Observed EMD &minus; null median</th><td class=\"num\">{:.6}</td></tr>\n\
+             <tr><th>Distance above noise</th><td class=\"num\">{:.4}</td></tr>\n\
+             </table>\n\
+             <p>The z-score is the number of standard deviations the observed EMD lies above \
+             the mean of the test distribution. The distance above noise is the fraction of \
+             the observed EMD above the 95th percentile of the test distribution: 0 means the \
+             difference is within the noise of random samples.</p>\n",
+            effect_size
+                .z_score
+                .map_or("&ndash;".to_string(), |z| format!("{z:.3}")),
+            effect_size.median_difference,
+            effect_size.distance_above_noise,
+        );
+
+        html.push_str("<h2>Test Distribution</h2>\n");
+        html.push_str(&self.render_histogram());
+
+        if let Some((language_1, language_2)) = self.languages {
+            html.push_str("<h2>Most Differing Variants</h2>\n");
+            html.push_str(&self.render_variants(language_1, language_2));
+        }
+
+        html.push_str("<h2>Parameters</h2>\n<table>\n");
+        let mut rows = vec![
+            ("Comparator".to_string(), metadata.comparator.clone()),
+            ("Seed".to_string(), format!("{:?}", metadata.seed)),
+            ("Cases (log 1)".to_string(), metadata.log_1_size.to_string()),
+            ("Cases (log 2)".to_string(), metadata.log_2_size.to_string()),
+            (
+                "Variants (log 1)".to_string(),
+                metadata.log_1_variants.to_string(),
+            ),
+            (
+                "Variants (log 2)".to_string(),
+                metadata.log_2_variants.to_string(),
+            ),
+        ];
+        if let Some(resample_size) = metadata.resample_size {
+            rows.push(("Resample size".to_string(), resample_size.to_string()));
+        }
+        rows.extend(metadata.parameters.iter().map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+            (key.clone(), value)
+        }));
+        rows.push((
+            "Runtime".to_string(),
+            format!("{:.3}s", metadata.runtime_seconds),
+        ));
+        rows.push(("Crate version".to_string(), metadata.crate_version.clone()));
+        rows.iter().for_each(|(key, value)| {
+            let _ = writeln!(
+                html,
+                "<tr><th>{}</th><td><code>{}</code></td></tr>",
+                escape(key),
+                escape(value)
+            );
+        });
+        html.push_str("</table>\n</body>\n</html>\n");
+
+        html
+    }
+
+    /// Write the rendered report to a file.
+    pub fn write(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.render())
+    }
+
+    /// Render a histogram of the test distribution as inline SVG, with a vertical
+    /// line marking the observed EMD.
+    fn render_histogram(&self) -> String {
+        let distribution = self.result.distribution();
+        let observed = self.result.logs_emd();
+
+        let (min, max) = distribution
+            .iter()
+            .chain(std::iter::once(&observed))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| {
+                (min.min(*x), max.max(*x))
+            });
+        // Avoid a zero-width range if all values are equal
+        let range = if max > min { max - min } else { 1.0 };
+
+        let mut counts = vec![0usize; self.num_bins];
+        distribution.iter().for_each(|emd| {
+            let bin = (((emd - min) / range) * self.num_bins as f64) as usize;
+            counts[bin.min(self.num_bins - 1)] += 1;
+        });
+        let max_count = counts.iter().copied().max().unwrap_or(0).max(1);
+
+        let plot_width = SVG_WIDTH - 2.0 * SVG_MARGIN;
+        let plot_height = SVG_HEIGHT - 2.0 * SVG_MARGIN;
+        let bar_width = plot_width / self.num_bins as f64;
+        let x_of = |value: f64| SVG_MARGIN + (value - min) / range * plot_width;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{SVG_WIDTH}\" height=\"{SVG_HEIGHT}\" \
+             viewBox=\"0 0 {SVG_WIDTH} {SVG_HEIGHT}\" font-size=\"12\">"
+        );
+        counts.iter().enumerate().for_each(|(i, count)| {
+            let height = *count as f64 / max_count as f64 * plot_height;
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"#6a9fd4\" \
+                 stroke=\"#fff\"><title>{count}</title></rect>",
+                SVG_MARGIN + i as f64 * bar_width,
+                SVG_MARGIN + plot_height - height,
+                bar_width,
+                height,
+            );
+        });
+        let baseline = SVG_MARGIN + plot_height;
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{SVG_MARGIN}\" y1=\"{baseline}\" x2=\"{}\" y2=\"{baseline}\" stroke=\"#222\"/>",
+            SVG_MARGIN + plot_width,
+        );
+        let _ = writeln!(
+            svg,
+            "<text x=\"{SVG_MARGIN}\" y=\"{}\" text-anchor=\"start\">{min:.4}</text>\n\
+             <text x=\"{}\" y=\"{}\" text-anchor=\"end\">{max:.4}</text>\n\
+             <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">EMD</text>\n\
+             <text x=\"{}\" y=\"{}\" text-anchor=\"end\">{max_count}</text>\n\
+             <text x=\"{}\" y=\"{baseline}\" text-anchor=\"end\">0</text>",
+            baseline + 16.0,
+            SVG_MARGIN + plot_width,
+            baseline + 16.0,
+            SVG_WIDTH / 2.0,
+            baseline + 32.0,
+            SVG_MARGIN - 4.0,
+            SVG_MARGIN + 4.0,
+            SVG_MARGIN - 4.0,
+        );
+        let observed_x = x_of(observed);
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{observed_x:.2}\" y1=\"{SVG_MARGIN}\" x2=\"{observed_x:.2}\" y2=\"{baseline}\" \
+             stroke=\"#d62728\" stroke-width=\"2\"/>\n\
+             <text x=\"{observed_x:.2}\" y=\"{}\" text-anchor=\"middle\" fill=\"#d62728\">\
+             observed EMD = {observed:.4}</text>\n</svg>",
+            SVG_MARGIN - 8.0,
+        );
+        svg
+    }
+
+    /// Render a table of the variants with the largest absolute difference in relative
+    /// frequency.
+    fn render_variants(
+        &self,
+        language_1: &StochasticLanguage<T>,
+        language_2: &StochasticLanguage<T>,
+    ) -> String {
+        let mut frequencies: HashMap<&T, (f64, f64)> = HashMap::new();
+        language_1
+            .variants
+            .iter()
+            .zip(language_1.frequencies.iter())
+            .for_each(|(variant, freq)| {
+                frequencies.entry(variant).or_default().0 += freq;
+            });
+        language_2
+            .variants
+            .iter()
+            .zip(language_2.frequencies.iter())
+            .for_each(|(variant, freq)| {
+                frequencies.entry(variant).or_default().1 += freq;
+            });
+
+        let mut table = String::from(
+            "<table>\n<tr><th>Variant</th><th>Log 1</th><th>Log 2</th><th>Difference</th></tr>\n",
+        );
+        frequencies
+            .into_iter()
+            .map(|(variant, (freq_1, freq_2))| (format!("{variant:?}"), freq_1, freq_2))
+            .sorted_by(|(variant_a, a_1, a_2), (variant_b, b_1, b_2)| {
+                (b_1 - b_2)
+                    .abs()
+                    .total_cmp(&(a_1 - a_2).abs())
+                    .then_with(|| variant_a.cmp(variant_b))
+            })
+            .take(self.top_variants)
+            .for_each(|(variant, freq_1, freq_2)| {
+                let _ = writeln!(
+                    table,
+                    "<tr><td><code>{}</code></td><td class=\"num\">{:.2}%</td>\
+                     <td class=\"num\">{:.2}%</td><td class=\"num\">{:+.2}%</td></tr>",
+                    escape(&variant),
+                    freq_1 * 100.0,
+                    freq_2 * 100.0,
+                    (freq_2 - freq_1) * 100.0,
+                );
+            });
+        table.push_str("</table>\n");
+        table
+    }
+}
+
+/// Escape the characters with a special meaning in HTML.
+fn escape(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&#39;"),
+                c => escaped.push(c),
+            }
+            escaped
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comparators::{
+        common::result::{ComparisonMetadata, CRATE_VERSION},
+        permutation_test::permutation_test_comparator::PermutationTestComparisonResult,
+    };
+    use crate::stats::{effect_size::EffectSize, pvalue::PValueEstimate};
+
+    #[test]
+    fn test_render_report() {
+        let result = PermutationTestComparisonResult {
+            logs_emd: 0.6,
+            permutation_emds: vec![0.1, 0.2, 0.2, 0.3, 0.7],
+            pvalue: 0.2,
+            pvalue_estimate: PValueEstimate::new(1, 5, &Default::default()),
+            effect_size: EffectSize::from_distribution(&[0.1, 0.2, 0.2, 0.3, 0.7], 0.6),
+            metadata: ComparisonMetadata {
+                comparator: "ControlFlowPermutationComparator".into(),
+                parameters: Default::default(),
+                seed: Some(1),
+                log_1_size: 3,
+                log_2_size: 2,
+                log_1_variants: 2,
+                log_2_variants: 1,
+                distribution_size: 5,
+                resample_size: None,
+                runtime_seconds: 0.5,
+                crate_version: CRATE_VERSION.into(),
+            },
+            sequential: None,
+        };
+        let language_1 = StochasticLanguage::from_items(vec!["<a>", "<a>", "b"]);
+        let language_2 = StochasticLanguage::from_items(vec!["b", "b", "c"]);
+
+        let html = ComparisonReport::new(&result)
+            .with_languages(&language_1, &language_2)
+            .with_bins(4)
+            .render();
+
+        assert!(html.contains("<svg"));
+        assert!(html.contains("observed EMD = 0.6000"));
+        assert_eq!(html.matches("<rect").count(), 4);
+        // The variant label is escaped, and the most differing variant comes first
+        let first_variant = html.find("&quot;&lt;a&gt;&quot;").unwrap();
+        assert!(first_variant < html.find("&quot;b&quot;").unwrap());
+        // No external resources
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("src=\""));
+    }
+}
@@ -0,0 +1,38 @@
+//! Representations of traces (and cases) extracted from event logs, which the
+//! distance functions in [`distance`](crate::distance) are defined on.
+
+use crate::utils::ordered_float::OrderedFloat;
+
+/// An event consisting of an activity, a binned service time, and a binned waiting
+/// time.
+///
+/// The weights of the components and the scaling of bin differences are not part
+/// of the event, but of the edit costs, see [`ServiceWaitingTimeCosts`].
+///
+/// [`ServiceWaitingTimeCosts`]: crate::distance::weighted_levenshtein::ServiceWaitingTimeCosts
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ServiceWaitingTimeEvent {
+    pub activity: String,
+    pub service_time: usize,
+    pub waiting_time: usize,
+}
+
+/// A trace of [`ServiceWaitingTimeEvent`]s.
+pub type ServiceWaitingTimeTrace = Vec<ServiceWaitingTimeEvent>;
+
+/// A single value of a projection of a case (or event) on its attributes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ProjectedValue {
+    /// A numeric value, scaled to `[0, 1]`.
+    Numeric(OrderedFloat),
+    /// A categorical value.
+    Categorical(String),
+    /// A placeholder for a missing value.
+    Missing,
+}
+
+/// A tuple of [`ProjectedValue`]s, one for each projected attribute.
+pub type ProjectedAttributes = Vec<ProjectedValue>;
+
+/// A trace of events projected on their attributes.
+pub type ProjectedTrace = Vec<ProjectedAttributes>;
@@ -0,0 +1,50 @@
+use super::special::inverse_regularized_incomplete_beta;
+
+/// The exact (Clopper-Pearson) confidence interval for a binomial proportion, e.g.,
+/// a Monte Carlo p-value estimated as `successes / trials`.
+///
+/// * `successes`: The number of successes, e.g., the number of EMDs in the test
+///   distribution exceeding the observed EMD.
+/// * `trials`: The number of trials, e.g., the size of the test distribution.
+/// * `confidence`: The confidence level, e.g., `0.95`.
+///
+/// Returns `(0, 1)` if there are no trials.
+pub fn clopper_pearson_interval(successes: usize, trials: usize, confidence: f64) -> (f64, f64) {
+    if trials == 0 {
+        return (0.0, 1.0);
+    }
+    let alpha = 1.0 - confidence;
+    let (k, n) = (successes as f64, trials as f64);
+
+    let lower = if successes == 0 {
+        0.0
+    } else {
+        inverse_regularized_incomplete_beta(alpha / 2.0, k, n - k + 1.0)
+    };
+    let upper = if successes >= trials {
+        1.0
+    } else {
+        inverse_regularized_incomplete_beta(1.0 - alpha / 2.0, k + 1.0, n - k)
+    };
+    (lower, upper)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clopper_pearson_interval() {
+        // Reference values from R: binom.test(5, 100)$conf.int
+        let (lower, upper) = clopper_pearson_interval(5, 100, 0.95);
+        assert!((lower - 0.016_431_18).abs() < 1e-6);
+        assert!((upper - 0.112_835_24).abs() < 1e-6);
+
+        // Zero successes: The upper bound is 1 - (alpha / 2)^(1 / n)
+        let (lower, upper) = clopper_pearson_interval(0, 50, 0.95);
+        assert_eq!(lower, 0.0);
+        assert!((upper - (1.0 - 0.025_f64.powf(1.0 / 50.0))).abs() < 1e-9);
+
+        assert_eq!(clopper_pearson_interval(0, 0, 0.95), (0.0, 1.0));
+    }
+}
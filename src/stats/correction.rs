@@ -0,0 +1,156 @@
+//! Multiple-testing corrections: When many comparisons are made (e.g., one baseline
+//! against many event logs, or many windows in drift detection), some of them are
+//! significant by chance. The corrections adjust the p-values to control the
+//! family-wise error rate (FWER) or the false discovery rate (FDR).
+
+use serde::{Deserialize, Serialize};
+
+use crate::comparators::common::result::ComparisonResult;
+
+/// A multiple-testing correction method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CorrectionMethod {
+    /// Controls the FWER by multiplying each p-value with the number of tests.
+    Bonferroni,
+    /// Holm's step-down procedure. Controls the FWER, and is uniformly more powerful
+    /// than Bonferroni.
+    Holm,
+    /// The Benjamini-Hochberg procedure. Controls the FDR for independent or
+    /// positively dependent tests.
+    BenjaminiHochberg,
+    /// The Benjamini-Yekutieli procedure. Controls the FDR under arbitrary dependence
+    /// between the tests, e.g., for overlapping windows in drift detection.
+    BenjaminiYekutieli,
+}
+
+/// The outcome of a multiple-testing correction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorrectedPValues {
+    pub method: CorrectionMethod,
+    /// The FWER or FDR level (depending on the method) the rejections are based on.
+    pub level: f64,
+    /// The uncorrected p-values, in the order of the tests.
+    pub pvalues: Vec<f64>,
+    /// The adjusted p-values, in the order of the tests.
+    pub adjusted_pvalues: Vec<f64>,
+    /// Whether the null hypothesis of each test is rejected, i.e., whether its
+    /// adjusted p-value is at most `level`.
+    pub rejected: Vec<bool>,
+}
+
+impl CorrectedPValues {
+    /// Adjust `pvalues` using `method` and reject the tests at the FWER or FDR `level`.
+    pub fn new(pvalues: &[f64], method: CorrectionMethod, level: f64) -> Self {
+        let adjusted_pvalues = adjust_pvalues(pvalues, method);
+        let rejected = adjusted_pvalues.iter().map(|p| *p <= level).collect();
+        Self {
+            method,
+            level,
+            pvalues: pvalues.to_vec(),
+            adjusted_pvalues,
+            rejected,
+        }
+    }
+
+    /// Correct the p-values of comparison results, using the p-values `(b+1)/(m+1)`.
+    /// Unlike the p-values `b/m`, these are never 0, so the corrections have an effect
+    /// even when no sampled EMD exceeds the observed EMD.
+    pub fn from_results<'a, R: ComparisonResult + 'a>(
+        results: impl IntoIterator<Item = &'a R>,
+        method: CorrectionMethod,
+        level: f64,
+    ) -> Self {
+        let pvalues: Vec<f64> = results
+            .into_iter()
+            .map(|result| result.pvalue_estimate().unbiased)
+            .collect();
+        Self::new(&pvalues, method, level)
+    }
+
+    /// The number of rejected null hypotheses.
+    pub fn num_rejected(&self) -> usize {
+        self.rejected.iter().filter(|rejected| **rejected).count()
+    }
+}
+
+/// Adjust p-values for multiple testing. The adjusted p-values are in the order of
+/// `pvalues`, and equal those of R's `p.adjust`.
+pub fn adjust_pvalues(pvalues: &[f64], method: CorrectionMethod) -> Vec<f64> {
+    let n = pvalues.len();
+    let num_tests = n as f64;
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|a, b| pvalues[*a].total_cmp(&pvalues[*b]));
+
+    let mut adjusted = vec![0.0; n];
+    match method {
+        CorrectionMethod::Bonferroni => {
+            pvalues
+                .iter()
+                .zip(adjusted.iter_mut())
+                .for_each(|(p, adjusted)| *adjusted = (p * num_tests).min(1.0));
+        }
+        CorrectionMethod::Holm => {
+            // Step down from the smallest p-value, keeping the adjusted p-values monotone
+            let mut running_max: f64 = 0.0;
+            order.iter().enumerate().for_each(|(rank, index)| {
+                let p = (pvalues[*index] * (n - rank) as f64).min(1.0);
+                running_max = running_max.max(p);
+                adjusted[*index] = running_max;
+            });
+        }
+        CorrectionMethod::BenjaminiHochberg | CorrectionMethod::BenjaminiYekutieli => {
+            let dependence_factor = if method == CorrectionMethod::BenjaminiYekutieli {
+                (1..=n).map(|k| 1.0 / k as f64).sum()
+            } else {
+                1.0
+            };
+            // Step up from the largest p-value, keeping the adjusted p-values monotone
+            let mut running_min: f64 = 1.0;
+            order.iter().enumerate().rev().for_each(|(rank, index)| {
+                let p = pvalues[*index] * dependence_factor * num_tests / (rank + 1) as f64;
+                running_min = running_min.min(p);
+                adjusted[*index] = running_min;
+            });
+        }
+    }
+    adjusted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_all_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        actual
+            .iter()
+            .zip(expected)
+            .for_each(|(a, e)| assert!((a - e).abs() < 1e-6, "{actual:?} != {expected:?}"));
+    }
+
+    #[test]
+    fn test_adjust_pvalues() {
+        // Expected values computed with R's p.adjust
+        let pvalues = [0.01, 0.04, 0.03, 0.005];
+        assert_all_close(
+            &adjust_pvalues(&pvalues, CorrectionMethod::Bonferroni),
+            &[0.04, 0.16, 0.12, 0.02],
+        );
+        assert_all_close(
+            &adjust_pvalues(&pvalues, CorrectionMethod::Holm),
+            &[0.03, 0.06, 0.06, 0.02],
+        );
+        assert_all_close(
+            &adjust_pvalues(&pvalues, CorrectionMethod::BenjaminiHochberg),
+            &[0.02, 0.04, 0.04, 0.02],
+        );
+        assert_all_close(
+            &adjust_pvalues(&pvalues, CorrectionMethod::BenjaminiYekutieli),
+            &[0.0416667, 0.0833333, 0.0833333, 0.0416667],
+        );
+
+        let corrected = CorrectedPValues::new(&pvalues, CorrectionMethod::Holm, 0.05);
+        assert_eq!(corrected.rejected, vec![true, false, false, true]);
+        assert_eq!(corrected.num_rejected(), 2);
+    }
+}
@@ -0,0 +1,111 @@
+//! Effect sizes, quantifying _how_ different two event logs are, independent of the
+//! number of cases. With large event logs, even tiny differences are significant,
+//! so the p-value should be interpreted alongside an effect size.
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::nullable_float;
+
+/// Effect sizes of the observed EMD relative to the test (null) distribution.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EffectSize {
+    /// The mean of the null distribution.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub null_mean: f64,
+    /// The (sample) standard deviation of the null distribution.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub null_std: f64,
+    /// The median of the null distribution.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub null_median: f64,
+    /// The 95th percentile of the null distribution.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub null_q95: f64,
+    /// The observed EMD standardized against the null distribution,
+    /// `(observed - null_mean) / null_std`. The number of standard deviations the
+    /// observed EMD lies above the EMDs expected by chance. `None` if the null
+    /// distribution has no variance.
+    pub z_score: Option<f64>,
+    /// `observed - null_median`, in the unit of the EMD (i.e., of the cost function).
+    /// How much larger the observed EMD is than a typical EMD under the null hypothesis.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub median_difference: f64,
+    /// The fraction of the observed EMD above the 95th percentile of the null
+    /// distribution, `max(0, observed - null_q95) / observed`, in `[0, 1]`. A value
+    /// of 0 means that the observed EMD is within the noise of random samples;
+    /// values close to 1 mean that the observed EMD is almost entirely due to an
+    /// actual difference between the event logs.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub distance_above_noise: f64,
+}
+
+impl EffectSize {
+    /// Compute the effect sizes of `observed_emd` with respect to a null distribution.
+    pub fn from_distribution(distribution: &[f64], observed_emd: f64) -> Self {
+        let mut sorted = distribution.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        let n = sorted.len() as f64;
+        let null_mean = sorted.iter().sum::<f64>() / n;
+        let null_std = if sorted.len() > 1 {
+            (sorted.iter().map(|x| (x - null_mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+        let null_median = quantile(&sorted, 0.5);
+        let null_q95 = quantile(&sorted, 0.95);
+
+        Self {
+            null_mean,
+            null_std,
+            null_median,
+            null_q95,
+            z_score: (null_std > 0.0).then(|| (observed_emd - null_mean) / null_std),
+            median_difference: observed_emd - null_median,
+            distance_above_noise: if observed_emd > 0.0 {
+                (observed_emd - null_q95).max(0.0) / observed_emd
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+/// The `q`-quantile of sorted data using linear interpolation between the closest
+/// ranks. `NaN` for empty data.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    let position = q * (sorted.len() - 1) as f64;
+    let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effect_size() {
+        let distribution = [0.4, 0.1, 0.3, 0.2, 0.5];
+        let effect_size = EffectSize::from_distribution(&distribution, 1.0);
+
+        assert!((effect_size.null_mean - 0.3).abs() < 1e-12);
+        assert!((effect_size.null_median - 0.3).abs() < 1e-12);
+        assert!((effect_size.null_q95 - 0.48).abs() < 1e-12);
+        let std = 0.025_f64.sqrt();
+        assert!((effect_size.z_score.unwrap() - 0.7 / std).abs() < 1e-9);
+        assert!((effect_size.median_difference - 0.7).abs() < 1e-12);
+        assert!((effect_size.distance_above_noise - 0.52).abs() < 1e-12);
+
+        // Within the noise
+        let effect_size = EffectSize::from_distribution(&distribution, 0.3);
+        assert_eq!(effect_size.distance_above_noise, 0.0);
+        // No variance
+        assert_eq!(
+            EffectSize::from_distribution(&[0.2, 0.2], 0.5).z_score,
+            None
+        );
+    }
+}
@@ -0,0 +1,10 @@
+//! Statistical utilities for the tests, e.g., confidence intervals for p-values,
+//! effect sizes, sequential (early stopping) Monte Carlo tests and multiple-testing
+//! corrections.
+
+pub mod confidence;
+pub mod correction;
+pub mod effect_size;
+pub mod pvalue;
+pub mod sequential;
+mod special;
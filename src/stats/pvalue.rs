@@ -0,0 +1,170 @@
+//! Monte Carlo p-value estimates with their uncertainty.
+
+use serde::{Deserialize, Serialize};
+
+use super::confidence::clopper_pearson_interval;
+use crate::utils::nullable_float;
+
+/// How a sampled EMD is compared to the observed EMD to count as an _exceedance_.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExceedanceComparison {
+    /// The sampled EMD is strictly greater than the observed EMD.
+    #[default]
+    Greater,
+    /// The sampled EMD is greater than or equal to the observed EMD.
+    GreaterOrEqual,
+}
+
+impl ExceedanceComparison {
+    pub fn exceeds(&self, sampled_emd: f64, observed_emd: f64) -> bool {
+        match self {
+            ExceedanceComparison::Greater => sampled_emd > observed_emd,
+            ExceedanceComparison::GreaterOrEqual => sampled_emd >= observed_emd,
+        }
+    }
+}
+
+/// Options for computing the p-value from the test distribution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PValueOptions {
+    comparison: ExceedanceComparison,
+    confidence: f64,
+}
+
+impl Default for PValueOptions {
+    /// Count strictly greater EMDs and compute a 95% confidence interval.
+    fn default() -> Self {
+        Self {
+            comparison: ExceedanceComparison::Greater,
+            confidence: 0.95,
+        }
+    }
+}
+
+impl PValueOptions {
+    pub fn new(comparison: ExceedanceComparison, confidence: f64) -> Self {
+        Self {
+            comparison,
+            confidence,
+        }
+    }
+
+    pub fn with_comparison(mut self, comparison: ExceedanceComparison) -> Self {
+        self.comparison = comparison;
+        self
+    }
+
+    /// The confidence level of the Clopper-Pearson interval.
+    pub fn with_confidence(mut self, confidence: f64) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    pub fn comparison(&self) -> ExceedanceComparison {
+        self.comparison
+    }
+
+    pub fn confidence(&self) -> f64 {
+        self.confidence
+    }
+}
+
+/// A Monte Carlo estimate of a p-value from `samples` sampled EMDs, of which
+/// `exceedances` exceed the observed EMD.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PValueEstimate {
+    /// The number of sampled EMDs exceeding the observed EMD.
+    pub exceedances: usize,
+    /// The number of sampled EMDs.
+    pub samples: usize,
+    pub comparison: ExceedanceComparison,
+    /// The fraction of exceeding EMDs, `exceedances / samples`. Without samples, 1
+    /// (like `unbiased`), as there is no evidence against the null hypothesis.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub raw: f64,
+    /// The p-value `(exceedances + 1) / (samples + 1)`, which counts the observed
+    /// EMD as part of the distribution. Never 0, and valid in the sense that
+    /// `P(p <= alpha) <= alpha` under the null hypothesis.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub unbiased: f64,
+    /// The standard error of the raw p-value, `sqrt(raw * (1 - raw) / samples)`.
+    /// Without samples, 0; The uncertainty is reflected in the confidence interval
+    /// `[0, 1]` instead.
+    #[serde(deserialize_with = "nullable_float::deserialize")]
+    pub standard_error: f64,
+    /// The confidence level of `confidence_interval`.
+    pub confidence: f64,
+    /// The Clopper-Pearson confidence interval of the p-value.
+    #[serde(deserialize_with = "nullable_float::deserialize_pair")]
+    pub confidence_interval: (f64, f64),
+}
+
+impl PValueEstimate {
+    /// Estimate the p-value from the number of exceedances in `samples` samples.
+    pub fn new(exceedances: usize, samples: usize, options: &PValueOptions) -> Self {
+        let raw = if samples == 0 {
+            1.0
+        } else {
+            exceedances as f64 / samples as f64
+        };
+        Self {
+            exceedances,
+            samples,
+            comparison: options.comparison,
+            raw,
+            unbiased: (exceedances + 1) as f64 / (samples + 1) as f64,
+            standard_error: (raw * (1.0 - raw) / samples.max(1) as f64).sqrt(),
+            confidence: options.confidence,
+            confidence_interval: clopper_pearson_interval(exceedances, samples, options.confidence),
+        }
+    }
+
+    /// Estimate the p-value of `observed_emd` from a test distribution.
+    pub fn from_distribution(
+        distribution: &[f64],
+        observed_emd: f64,
+        options: &PValueOptions,
+    ) -> Self {
+        let exceedances = distribution
+            .iter()
+            .filter(|emd| options.comparison.exceeds(**emd, observed_emd))
+            .count();
+        Self::new(exceedances, distribution.len(), options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pvalue_estimate() {
+        let distribution = [0.1, 0.2, 0.5, 0.5, 0.9];
+        let options = PValueOptions::default();
+
+        let estimate = PValueEstimate::from_distribution(&distribution, 0.5, &options);
+        assert_eq!(estimate.exceedances, 1);
+        assert_eq!(estimate.raw, 0.2);
+        assert_eq!(estimate.unbiased, 2.0 / 6.0);
+        assert!((estimate.standard_error - (0.2_f64 * 0.8 / 5.0).sqrt()).abs() < 1e-12);
+
+        let estimate = PValueEstimate::from_distribution(
+            &distribution,
+            0.5,
+            &options.with_comparison(ExceedanceComparison::GreaterOrEqual),
+        );
+        assert_eq!(estimate.exceedances, 3);
+
+        // The unbiased p-value is never 0
+        let estimate = PValueEstimate::from_distribution(&distribution, 1.0, &options);
+        assert_eq!(estimate.raw, 0.0);
+        assert_eq!(estimate.unbiased, 1.0 / 6.0);
+
+        // Without samples, there is no evidence against the null hypothesis
+        let estimate = PValueEstimate::from_distribution(&[], 0.5, &options);
+        assert_eq!(estimate.raw, 1.0);
+        assert_eq!(estimate.unbiased, 1.0);
+        assert_eq!(estimate.standard_error, 0.0);
+        assert_eq!(estimate.confidence_interval, (0.0, 1.0));
+    }
+}
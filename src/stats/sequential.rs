@@ -0,0 +1,227 @@
+//! Sequential Monte Carlo tests: Stop computing the test distribution as soon as
+//! the p-value is confidently above or below the significance level.
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    confidence::clopper_pearson_interval,
+    pvalue::{ExceedanceComparison, PValueOptions},
+};
+
+/// The rule deciding when to stop computing the test distribution.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StoppingRule {
+    /// Stop once the Clopper-Pearson confidence interval of the p-value lies entirely
+    /// below or above the significance level.
+    ConfidenceInterval,
+    /// The sequential procedure proposed in "Sequential Monte Carlo p-values" by
+    /// Besag and Clifford: Stop once `h` EMDs greater than the observed EMD have
+    /// been computed. Stops early mostly for large p-values.
+    BesagClifford { h: usize },
+}
+
+/// Options for a sequential test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequentialOptions {
+    alpha: f64,
+    rule: StoppingRule,
+    pvalue_options: PValueOptions,
+    min_samples: usize,
+    check_interval: usize,
+}
+
+impl SequentialOptions {
+    /// Stop based on the 99% Clopper-Pearson confidence interval, checked every 10
+    /// samples after at least 100 samples.
+    pub fn new(alpha: f64) -> Self {
+        Self {
+            alpha,
+            rule: StoppingRule::ConfidenceInterval,
+            pvalue_options: PValueOptions::default().with_confidence(0.99),
+            min_samples: 100,
+            check_interval: 10,
+        }
+    }
+
+    pub fn with_stopping_rule(mut self, rule: StoppingRule) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    /// The confidence level of the confidence interval for the p-value, both for the
+    /// [`StoppingRule::ConfidenceInterval`] and for the reported interval.
+    pub fn with_confidence(mut self, confidence: f64) -> Self {
+        self.pvalue_options = self.pvalue_options.with_confidence(confidence);
+        self
+    }
+
+    /// How sampled EMDs are compared to the observed EMD.
+    pub fn with_exceedance_comparison(mut self, comparison: ExceedanceComparison) -> Self {
+        self.pvalue_options = self.pvalue_options.with_comparison(comparison);
+        self
+    }
+
+    /// The minimum number of samples before the [`StoppingRule::ConfidenceInterval`]
+    /// may stop.
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples;
+        self
+    }
+
+    /// Check the [`StoppingRule::ConfidenceInterval`] every `check_interval` samples.
+    pub fn with_check_interval(mut self, check_interval: usize) -> Self {
+        self.check_interval = check_interval.max(1);
+        self
+    }
+
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    pub fn pvalue_options(&self) -> &PValueOptions {
+        &self.pvalue_options
+    }
+}
+
+/// How the test distribution is computed: With a fixed size, or sequentially.
+#[derive(Debug, Clone, Copy)]
+pub enum SamplingMode<'a> {
+    /// Compute a fixed number of samples.
+    Fixed(&'a PValueOptions),
+    /// Compute samples until the stopping rule is met.
+    Sequential(&'a SequentialOptions),
+}
+
+impl SamplingMode<'_> {
+    pub fn pvalue_options(&self) -> &PValueOptions {
+        match self {
+            SamplingMode::Fixed(options) => options,
+            SamplingMode::Sequential(options) => &options.pvalue_options,
+        }
+    }
+}
+
+/// The conclusion of a sequential test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SequentialDecision {
+    /// The confidence interval of the p-value lies below alpha.
+    Significant,
+    /// The confidence interval of the p-value lies above alpha.
+    NotSignificant,
+    /// The confidence interval of the p-value contains alpha.
+    Undecided,
+}
+
+/// A summary of a sequential test. The p-value estimate and its confidence interval
+/// are part of the comparison result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SequentialSummary {
+    /// The number of samples (permutations or bootstrap samples) actually computed.
+    pub samples_used: usize,
+    /// The maximum number of samples.
+    pub max_samples: usize,
+    /// Whether the test stopped before computing `max_samples` samples.
+    pub stopped_early: bool,
+    pub decision: SequentialDecision,
+    /// The significance level the decision is based on.
+    pub alpha: f64,
+}
+
+/// Draw samples (EMDs) until the stopping rule is met, or `max_samples` samples
+/// have been drawn.
+///
+/// Returns the drawn samples and a summary of the sequential test.
+pub fn sequential_distribution(
+    samples: impl Iterator<Item = f64>,
+    observed_emd: f64,
+    max_samples: usize,
+    options: &SequentialOptions,
+) -> (Vec<f64>, SequentialSummary) {
+    let mut distribution = Vec::new();
+    let mut exceedances = 0;
+
+    for emd in samples.take(max_samples) {
+        distribution.push(emd);
+        if options
+            .pvalue_options
+            .comparison()
+            .exceeds(emd, observed_emd)
+        {
+            exceedances += 1;
+        }
+
+        let n = distribution.len();
+        let stop = match options.rule {
+            StoppingRule::BesagClifford { h } => exceedances >= h,
+            StoppingRule::ConfidenceInterval => {
+                n >= options.min_samples
+                    && n % options.check_interval == 0
+                    && decide(
+                        clopper_pearson_interval(
+                            exceedances,
+                            n,
+                            options.pvalue_options.confidence(),
+                        ),
+                        options.alpha,
+                    ) != SequentialDecision::Undecided
+            }
+        };
+        if stop {
+            break;
+        }
+    }
+
+    let samples_used = distribution.len();
+    let confidence_interval = clopper_pearson_interval(
+        exceedances,
+        samples_used,
+        options.pvalue_options.confidence(),
+    );
+    let summary = SequentialSummary {
+        samples_used,
+        max_samples,
+        stopped_early: samples_used < max_samples,
+        decision: decide(confidence_interval, options.alpha),
+        alpha: options.alpha,
+    };
+    (distribution, summary)
+}
+
+fn decide((lower, upper): (f64, f64), alpha: f64) -> SequentialDecision {
+    if upper < alpha {
+        SequentialDecision::Significant
+    } else if lower > alpha {
+        SequentialDecision::NotSignificant
+    } else {
+        SequentialDecision::Undecided
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stops_early_when_obviously_significant() {
+        let options = SequentialOptions::new(0.05);
+        let (distribution, summary) =
+            sequential_distribution(std::iter::repeat(0.1), 1.0, 10_000, &options);
+
+        assert!(summary.stopped_early);
+        assert_eq!(summary.decision, SequentialDecision::Significant);
+        assert_eq!(distribution.len(), summary.samples_used);
+        assert!(summary.samples_used < 1_000);
+    }
+
+    #[test]
+    fn test_besag_clifford() {
+        let options =
+            SequentialOptions::new(0.05).with_stopping_rule(StoppingRule::BesagClifford { h: 5 });
+        let samples = [0.5, 2.0].into_iter().cycle();
+        let (distribution, summary) = sequential_distribution(samples, 1.0, 10_000, &options);
+
+        assert_eq!(distribution.len(), 10);
+        assert_eq!(summary.samples_used, 10);
+        assert_eq!(summary.decision, SequentialDecision::NotSignificant);
+    }
+}
@@ -0,0 +1,101 @@
+//! Special functions needed for the binomial confidence intervals.
+
+/// The natural logarithm of the gamma function, using the Lanczos approximation.
+pub(crate) fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(1.000_000_000_190_015, |acc, (i, c)| {
+            acc + c / (x + 1.0 + i as f64)
+        });
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+/// The regularized incomplete beta function `I_x(a, b)`.
+pub(crate) fn regularized_incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let ln_front = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln();
+    // The continued fraction converges quickly for x < (a + 1) / (a + b + 2). Otherwise,
+    // use the symmetry I_x(a, b) = 1 - I_{1-x}(b, a).
+    if x < (a + 1.0) / (a + b + 2.0) {
+        ln_front.exp() * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - ln_front.exp() * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// The continued fraction for the incomplete beta function (modified Lentz's method).
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 300;
+    const EPSILON: f64 = 1e-14;
+    const TINY: f64 = 1e-300;
+
+    let clamp = |v: f64| if v.abs() < TINY { TINY } else { v };
+
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut result = d;
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + even * d);
+        c = clamp(1.0 + even / c);
+        result *= d * c;
+
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + odd * d);
+        c = clamp(1.0 + odd / c);
+        let delta = d * c;
+        result *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    result
+}
+
+/// The inverse of the regularized incomplete beta function in `x`, i.e., the
+/// `probability`-quantile of the Beta(a, b) distribution. Computed by bisection.
+pub(crate) fn inverse_regularized_incomplete_beta(probability: f64, a: f64, b: f64) -> f64 {
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        if regularized_incomplete_beta(mid, a, b) < probability {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_incomplete_beta() {
+        // I_x(1, 1) is the CDF of the uniform distribution
+        assert!((regularized_incomplete_beta(0.3, 1.0, 1.0) - 0.3).abs() < 1e-10);
+        // I_x(2, 3) = 1 - (1 - x)^4 - 4x(1 - x)^3
+        let x: f64 = 0.4;
+        let expected = 1.0 - (1.0 - x).powi(4) - 4.0 * x * (1.0 - x).powi(3);
+        assert!((regularized_incomplete_beta(x, 2.0, 3.0) - expected).abs() < 1e-10);
+        assert!((inverse_regularized_incomplete_beta(expected, 2.0, 3.0) - x).abs() < 1e-10);
+    }
+}
@@ -25,6 +25,12 @@ pub enum AttributeErrorKind {
     MissingAttribute,
     #[error("has unexpected type. Expected {0}, found {1:?}")]
     TypeMismatch(String, AttributeValue),
+    #[error("could not be coerced to {target}. Tried {}, found {found:?}", .attempted.join(", "))]
+    CoercionFailed {
+        target: String,
+        attempted: Vec<String>,
+        found: AttributeValue,
+    },
 }
 
 /// An error that can occur when accessing attributes.
@@ -0,0 +1,286 @@
+use process_mining::event_log::AttributeValue;
+use serde::Serialize;
+
+/// Configuration of the coercions applied when reading numeric attributes with
+/// [`get_numeric_by_key`] and [`get_numeric_int_by_key`].
+///
+/// Values that already have the requested type are always accepted.
+///
+/// [`get_numeric_by_key`]: super::HasAttributes::get_numeric_by_key
+/// [`get_numeric_int_by_key`]: super::HasAttributes::get_numeric_int_by_key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct NumericCoercion {
+    /// Accept Ints where a float is requested.
+    pub int_to_float: bool,
+    /// Accept Floats without a fractional part where an int is requested.
+    pub float_to_int: bool,
+    /// Accept Strings containing a number, e.g., `"12.5"`.
+    pub numeric_strings: bool,
+    /// Accept Booleans as `0` (`false`) and `1` (`true`).
+    pub booleans: bool,
+    /// Accept Strings containing an ISO 8601 duration (`xs:duration`), e.g.,
+    /// `"PT1H30M"`, which are converted to seconds. See [`parse_iso8601_duration`].
+    pub durations: bool,
+}
+
+impl Default for NumericCoercion {
+    /// All coercions are enabled (see [`NumericCoercion::lenient`]).
+    fn default() -> Self {
+        Self::lenient()
+    }
+}
+
+impl NumericCoercion {
+    /// Enable all coercions.
+    pub fn lenient() -> Self {
+        Self {
+            int_to_float: true,
+            float_to_int: true,
+            numeric_strings: true,
+            booleans: true,
+            durations: true,
+        }
+    }
+
+    /// Disable all coercions, i.e., only accept values that already have the
+    /// requested type.
+    pub fn strict() -> Self {
+        Self {
+            int_to_float: false,
+            float_to_int: false,
+            numeric_strings: false,
+            booleans: false,
+            durations: false,
+        }
+    }
+
+    /// Coerce an attribute value to a float. Returns `None` if this is not possible
+    /// with the enabled coercions.
+    pub fn coerce_to_float(&self, value: &AttributeValue) -> Option<f64> {
+        match value {
+            AttributeValue::Float(value) => Some(*value),
+            AttributeValue::Int(value) if self.int_to_float => Some(*value as f64),
+            AttributeValue::Boolean(value) if self.booleans => Some(if *value { 1.0 } else { 0.0 }),
+            AttributeValue::String(value) => self.coerce_string_to_float(value),
+            _ => None,
+        }
+    }
+
+    /// Coerce an attribute value to an int. Returns `None` if this is not possible
+    /// with the enabled coercions, or if the value has a fractional part.
+    pub fn coerce_to_int(&self, value: &AttributeValue) -> Option<i64> {
+        match value {
+            AttributeValue::Int(value) => Some(*value),
+            AttributeValue::Float(value) if self.float_to_int => float_to_int(*value),
+            AttributeValue::Boolean(value) if self.booleans => Some(*value as i64),
+            AttributeValue::String(value) => {
+                let trimmed = value.trim();
+                if self.numeric_strings {
+                    if let Ok(value) = trimmed.parse::<i64>() {
+                        return Some(value);
+                    }
+                    if self.float_to_int {
+                        if let Some(value) = parse_finite_float(trimmed) {
+                            return float_to_int(value);
+                        }
+                    }
+                }
+                if self.durations {
+                    return parse_iso8601_duration(trimmed).and_then(float_to_int);
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// The types (and coercions) that are tried to read a float, used for error
+    /// messages.
+    pub fn attempted_float_types(&self) -> Vec<String> {
+        let mut attempted = vec!["Float".to_string()];
+        if self.int_to_float {
+            attempted.push("Int".to_string());
+        }
+        self.push_common_attempted_types(&mut attempted);
+        attempted
+    }
+
+    /// The types (and coercions) that are tried to read an int, used for error
+    /// messages.
+    pub fn attempted_int_types(&self) -> Vec<String> {
+        let mut attempted = vec!["Int".to_string()];
+        if self.float_to_int {
+            attempted.push("integral Float".to_string());
+        }
+        self.push_common_attempted_types(&mut attempted);
+        attempted
+    }
+
+    fn push_common_attempted_types(&self, attempted: &mut Vec<String>) {
+        if self.numeric_strings {
+            attempted.push("numeric String".to_string());
+        }
+        if self.booleans {
+            attempted.push("Boolean".to_string());
+        }
+        if self.durations {
+            attempted.push("ISO 8601 duration String".to_string());
+        }
+    }
+
+    fn coerce_string_to_float(&self, value: &str) -> Option<f64> {
+        let trimmed = value.trim();
+        if self.numeric_strings {
+            if let Some(value) = parse_finite_float(trimmed) {
+                return Some(value);
+            }
+        }
+        if self.durations {
+            return parse_iso8601_duration(trimmed);
+        }
+        None
+    }
+}
+
+/// Parse a finite float. Rust also parses `"NaN"`, `"inf"` and `"infinity"`, which are
+/// not meaningful attribute values.
+fn parse_finite_float(value: &str) -> Option<f64> {
+    value.parse::<f64>().ok().filter(|value| value.is_finite())
+}
+
+fn float_to_int(value: f64) -> Option<i64> {
+    if value.fract() == 0.0 && value >= i64::MIN as f64 && value <= i64::MAX as f64 {
+        Some(value as i64)
+    } else {
+        None
+    }
+}
+
+/// Parse an ISO 8601 duration (as used by `xs:duration`), e.g., `"P1DT2H30M"` or
+/// `"-PT0.5S"`, to seconds.
+///
+/// Supports weeks (`W`), days (`D`), hours (`H`), minutes (`M` after `T`) and
+/// seconds (`S`), where the last component may have a fractional part. Years and
+/// months are rejected since their length in seconds is ambiguous. Returns `None`
+/// if the string is not a valid duration, e.g., if a `T` is not followed by a time
+/// component.
+pub fn parse_iso8601_duration(value: &str) -> Option<f64> {
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, value),
+    };
+    let rest = rest.strip_prefix('P')?;
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut seconds = 0.0;
+    let mut in_time_part = false;
+    let mut number = String::new();
+    let mut last_unit = None;
+    let mut has_time_component = false;
+    for c in rest.chars() {
+        match c {
+            '0'..='9' | '.' | ',' => number.push(if c == ',' { '.' } else { c }),
+            'T' if !in_time_part && number.is_empty() => in_time_part = true,
+            unit => {
+                if number.is_empty() {
+                    return None;
+                }
+                let multiplier = match (unit, in_time_part) {
+                    ('W', false) => 7.0 * 24.0 * 3600.0,
+                    ('D', false) => 24.0 * 3600.0,
+                    ('H', true) => 3600.0,
+                    ('M', true) => 60.0,
+                    ('S', true) => 1.0,
+                    _ => return None,
+                };
+                // Components must be given in decreasing order of magnitude
+                if last_unit.is_some_and(|last| last <= multiplier) {
+                    return None;
+                }
+                last_unit = Some(multiplier);
+                has_time_component = in_time_part;
+                seconds += parse_finite_float(&number)? * multiplier;
+                number.clear();
+            }
+        }
+    }
+
+    if !number.is_empty() || last_unit.is_none() || (in_time_part && !has_time_component) {
+        return None;
+    }
+    Some(sign * seconds).filter(|seconds| seconds.is_finite())
+}
+
+/// The canonical string form of a primitive attribute value, e.g., to use it as a
+/// category: Strings as-is, numbers and booleans in their usual notation (so that
+/// `Int(1)` and `String("1")` coincide), dates in RFC 3339 and IDs as hyphenated
+/// UUIDs. `None` for lists, containers and `None` values.
+pub fn coerce_to_string(value: &AttributeValue) -> Option<String> {
+    match value {
+        AttributeValue::String(value) => Some(value.clone()),
+        AttributeValue::Int(value) => Some(value.to_string()),
+        AttributeValue::Float(value) => Some(value.to_string()),
+        AttributeValue::Boolean(value) => Some(value.to_string()),
+        AttributeValue::Date(value) => Some(value.to_rfc3339()),
+        AttributeValue::ID(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_iso8601_duration() {
+        assert_eq!(parse_iso8601_duration("PT1H30M"), Some(5400.0));
+        assert_eq!(parse_iso8601_duration("P1DT0.5S"), Some(86400.5));
+        assert_eq!(parse_iso8601_duration("-P1W"), Some(-604800.0));
+        assert_eq!(parse_iso8601_duration("P1M"), None);
+        assert_eq!(parse_iso8601_duration("PT"), None);
+        assert_eq!(parse_iso8601_duration("PT5M1H"), None);
+        assert_eq!(parse_iso8601_duration("1H"), None);
+        assert_eq!(parse_iso8601_duration("P1DT"), None);
+    }
+
+    #[test]
+    fn test_numeric_coercion() {
+        let lenient = NumericCoercion::lenient();
+        let strict = NumericCoercion::strict();
+
+        let int = AttributeValue::Int(3);
+        assert_eq!(lenient.coerce_to_float(&int), Some(3.0));
+        assert_eq!(strict.coerce_to_float(&int), None);
+
+        let float = AttributeValue::Float(3.5);
+        assert_eq!(lenient.coerce_to_int(&float), None);
+        assert_eq!(lenient.coerce_to_int(&AttributeValue::Float(3.0)), Some(3));
+
+        let string = AttributeValue::String(" 12.5 ".to_string());
+        assert_eq!(lenient.coerce_to_float(&string), Some(12.5));
+        assert_eq!(strict.coerce_to_float(&string), None);
+
+        let float_string = AttributeValue::String("3.0".to_string());
+        assert_eq!(lenient.coerce_to_int(&float_string), Some(3));
+        let no_float_to_int = NumericCoercion {
+            float_to_int: false,
+            ..lenient
+        };
+        assert_eq!(no_float_to_int.coerce_to_int(&float_string), None);
+
+        for non_finite in ["NaN", "inf", "-infinity"] {
+            let value = AttributeValue::String(non_finite.to_string());
+            assert_eq!(lenient.coerce_to_float(&value), None);
+        }
+
+        let duration = AttributeValue::String("PT2M".to_string());
+        assert_eq!(lenient.coerce_to_int(&duration), Some(120));
+
+        assert_eq!(
+            lenient.coerce_to_float(&AttributeValue::Boolean(true)),
+            Some(1.0)
+        );
+    }
+}
@@ -1,13 +1,15 @@
 pub mod attribute_error;
+pub mod coercion;
 
 use attribute_error::{AttributeError, AttributeErrorKind, AttributeLevel, AttributeResult};
 use chrono::{DateTime, FixedOffset};
+use coercion::NumericCoercion;
 use process_mining::event_log::{
     Attribute, AttributeValue, Attributes, Event, Trace, XESEditableAttribute,
 };
 
 use crate::utils::constants::{
-    ACTIVITY_KEY, INSTANCE_ID_KEY, LIFECYCLE_KEY, START_TIMESTAMP_KEY, TIMESTAMP_KEY,
+    ACTIVITY_KEY, INSTANCE_ID_KEY, LIFECYCLE_KEY, RESOURCE_KEY, START_TIMESTAMP_KEY, TIMESTAMP_KEY,
 };
 
 /// Helper-trait for a unified interface to attributes.
@@ -71,6 +73,48 @@ pub trait HasAttributes {
             )
         })
     }
+
+    /// Get a numeric attribute as a float, applying the configured [`NumericCoercion`],
+    /// e.g., to read Ints or numeric Strings as floats.
+    ///
+    /// If the value cannot be coerced, the error reports every type that was tried.
+    fn get_numeric_by_key(&self, key: &str, coercion: &NumericCoercion) -> AttributeResult<f64> {
+        let attribute = self.get_attribute_by_key(key)?;
+        coercion.coerce_to_float(&attribute.value).ok_or_else(|| {
+            AttributeError::new(
+                Self::ATTRIBUTE_LEVEL,
+                key,
+                AttributeErrorKind::CoercionFailed {
+                    target: "Float".to_string(),
+                    attempted: coercion.attempted_float_types(),
+                    found: attribute.value.clone(),
+                },
+            )
+        })
+    }
+
+    /// Get a numeric attribute as an int, applying the configured [`NumericCoercion`],
+    /// e.g., to read integral Floats or numeric Strings as ints.
+    ///
+    /// If the value cannot be coerced, the error reports every type that was tried.
+    fn get_numeric_int_by_key(
+        &self,
+        key: &str,
+        coercion: &NumericCoercion,
+    ) -> AttributeResult<i64> {
+        let attribute = self.get_attribute_by_key(key)?;
+        coercion.coerce_to_int(&attribute.value).ok_or_else(|| {
+            AttributeError::new(
+                Self::ATTRIBUTE_LEVEL,
+                key,
+                AttributeErrorKind::CoercionFailed {
+                    target: "Int".to_string(),
+                    attempted: coercion.attempted_int_types(),
+                    found: attribute.value.clone(),
+                },
+            )
+        })
+    }
 }
 
 impl HasAttributes for Trace {
@@ -155,3 +199,10 @@ pub fn get_lifecycle(event: &Event) -> AttributeResult<String> {
 pub fn get_instance_id(event: &Event) -> AttributeResult<String> {
     event.get_string_by_key(INSTANCE_ID_KEY)
 }
+
+/// Get the resource executing an event.
+///
+/// Returns an `Err` if the `org:resource` attribute does not exist or is not a string.
+pub fn get_resource(event: &Event) -> AttributeResult<String> {
+    event.get_string_by_key(RESOURCE_KEY)
+}
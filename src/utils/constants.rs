@@ -2,7 +2,11 @@
 
 pub const TRACEID_KEY: &str = "concept:name";
 pub const ACTIVITY_KEY: &str = "concept:name";
+pub const LOG_NAME_KEY: &str = "concept:name";
 pub const START_TIMESTAMP_KEY: &str = "start_timestamp";
 pub const TIMESTAMP_KEY: &str = "time:timestamp";
 pub const INSTANCE_ID_KEY: &str = "concept:instance";
 pub const LIFECYCLE_KEY: &str = "lifecycle:transition";
+pub const RESOURCE_KEY: &str = "org:resource";
+pub const ROLE_KEY: &str = "org:role";
+pub const GROUP_KEY: &str = "org:group";
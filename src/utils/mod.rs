@@ -1,4 +1,6 @@
 pub mod attributes;
 pub mod constants;
+pub mod nullable_float;
+pub mod ordered_float;
 pub mod progress;
 pub mod retain_err;
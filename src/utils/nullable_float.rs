@@ -0,0 +1,43 @@
+//! Deserialization of floats that may be non-finite, for use with
+//! `#[serde(deserialize_with = "...")]`.
+//!
+//! JSON cannot represent NaN or infinity, so `serde_json` writes them as `null`. The
+//! functions in this module read `null` back as NaN instead of failing, so that
+//! results containing, e.g., the EMD of empty languages can be reloaded.
+
+use serde::{Deserialize, Deserializer};
+
+/// A float, where `null` is read as NaN.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::NAN))
+}
+
+/// A list of floats, where `null` entries are read as NaN.
+pub fn deserialize_vec<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f64>, D::Error> {
+    Ok(Vec::<Option<f64>>::deserialize(deserializer)?
+        .into_iter()
+        .map(|value| value.unwrap_or(f64::NAN))
+        .collect())
+}
+
+/// A pair of floats, where `null` entries are read as NaN.
+pub fn deserialize_pair<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<(f64, f64), D::Error> {
+    let (first, second) = <(Option<f64>, Option<f64>)>::deserialize(deserializer)?;
+    Ok((first.unwrap_or(f64::NAN), second.unwrap_or(f64::NAN)))
+}
+
+/// A matrix of floats, where `null` entries are read as NaN.
+pub fn deserialize_matrix<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Vec<f64>>, D::Error> {
+    Ok(Vec::<Vec<Option<f64>>>::deserialize(deserializer)?
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|value| value.unwrap_or(f64::NAN))
+                .collect()
+        })
+        .collect())
+}
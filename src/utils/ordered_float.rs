@@ -0,0 +1,49 @@
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+
+use serde::Serialize;
+
+/// Wrapper around `f64` that implements [`Eq`], [`Ord`] and [`Hash`] using the
+/// IEEE 754 total order ([`f64::total_cmp`]).
+///
+/// This allows continuous values to be used as (parts of) representations in the
+/// comparators, which require representations to be hashable and totally ordered.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(transparent)]
+pub struct OrderedFloat(pub f64);
+
+impl PartialEq for OrderedFloat {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedFloat {}
+
+impl PartialOrd for OrderedFloat {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedFloat {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for OrderedFloat {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Consistent with `Eq`: `total_cmp` considers two floats equal iff their
+        // bit patterns are equal.
+        self.0.to_bits().hash(state);
+    }
+}
+
+impl From<f64> for OrderedFloat {
+    fn from(value: f64) -> Self {
+        Self(value)
+    }
+}
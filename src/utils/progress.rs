@@ -1,4 +1,10 @@
-use std::fmt::Write;
+//! Reporting the progress of long-running computations, such as distance matrices
+//! and test distributions. By default, progress is shown as a progress bar on stderr
+//! ([`IndicatifReporter`]); Embedding applications can silence it ([`SilentReporter`]),
+//! forward it to a callback or channel ([`CallbackReporter`]), or emit `log` records
+//! (`LogReporter`, with the `log` feature).
+
+use std::{cell::Cell, fmt::Debug, fmt::Write, sync::mpsc::Sender};
 
 use indicatif::{ProgressBar, ProgressState, ProgressStyle};
 
@@ -30,3 +36,287 @@ pub fn build_progress_bar(total: u64, message: String) -> ProgressBar {
         ),
     )
 }
+
+/// Decides how the progress of long-running computations is reported.
+pub trait ProgressReporter: Debug {
+    /// Start reporting the progress of a task consisting of `total` steps.
+    fn start(&self, total: u64, message: String) -> Box<dyn Progress + '_>;
+}
+
+/// The progress of a single task, see [`ProgressReporter::start`].
+pub trait Progress {
+    /// Advance the task by `delta` steps.
+    fn inc(&self, delta: u64);
+
+    /// Mark the task as finished.
+    fn finish(&self);
+}
+
+impl<R: ProgressReporter + ?Sized> ProgressReporter for Box<R> {
+    fn start(&self, total: u64, message: String) -> Box<dyn Progress + '_> {
+        self.as_ref().start(total, message)
+    }
+}
+
+impl Progress for ProgressBar {
+    fn inc(&self, delta: u64) {
+        ProgressBar::inc(self, delta)
+    }
+
+    fn finish(&self) {
+        ProgressBar::finish(self)
+    }
+}
+
+/// Show progress bars on stderr (see [`build_progress_bar`]). The default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IndicatifReporter;
+
+impl ProgressReporter for IndicatifReporter {
+    fn start(&self, total: u64, message: String) -> Box<dyn Progress + '_> {
+        Box::new(build_progress_bar(total, message))
+    }
+}
+
+/// Do not report any progress.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SilentReporter;
+
+struct NoProgress;
+
+impl Progress for NoProgress {
+    fn inc(&self, _delta: u64) {}
+
+    fn finish(&self) {}
+}
+
+impl ProgressReporter for SilentReporter {
+    fn start(&self, _total: u64, _message: String) -> Box<dyn Progress + '_> {
+        Box::new(NoProgress)
+    }
+}
+
+/// What happened to a task, see [`ProgressEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressEventKind {
+    Started,
+    Advanced,
+    Finished,
+}
+
+/// A change in the progress of a task, reported by a [`CallbackReporter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgressEvent {
+    pub kind: ProgressEventKind,
+    /// The description of the task.
+    pub message: String,
+    /// The number of completed steps.
+    pub position: u64,
+    /// The total number of steps.
+    pub total: u64,
+}
+
+impl ProgressEvent {
+    /// The completed percentage of the task, in `[0, 100]`.
+    pub fn percent(&self) -> u64 {
+        (self.position * 100).checked_div(self.total).unwrap_or(100)
+    }
+}
+
+/// The progress of a task reported as [`ProgressEvent`]s. To not emit an event per
+/// step, advancements are only emitted whenever the completed percentage crosses a
+/// multiple of `percent_step`.
+struct EventProgress<'a> {
+    emit: Box<dyn Fn(ProgressEvent) + 'a>,
+    message: String,
+    total: u64,
+    percent_step: u64,
+    position: Cell<u64>,
+}
+
+impl<'a> EventProgress<'a> {
+    fn start(
+        emit: Box<dyn Fn(ProgressEvent) + 'a>,
+        total: u64,
+        message: String,
+        percent_step: u64,
+    ) -> Self {
+        let progress = Self {
+            emit,
+            message,
+            total,
+            percent_step: percent_step.max(1),
+            position: Cell::new(0),
+        };
+        progress.emit(ProgressEventKind::Started);
+        progress
+    }
+
+    fn emit(&self, kind: ProgressEventKind) {
+        (self.emit)(ProgressEvent {
+            kind,
+            message: self.message.clone(),
+            position: self.position.get(),
+            total: self.total,
+        })
+    }
+
+    fn step(&self) -> u64 {
+        (self.position.get() * 100)
+            .checked_div(self.total)
+            .unwrap_or(100)
+            / self.percent_step
+    }
+}
+
+impl Progress for EventProgress<'_> {
+    fn inc(&self, delta: u64) {
+        let step = self.step();
+        self.position.set(self.position.get() + delta);
+        if self.step() != step {
+            self.emit(ProgressEventKind::Advanced);
+        }
+    }
+
+    fn finish(&self) {
+        self.emit(ProgressEventKind::Finished);
+    }
+}
+
+/// Pass [`ProgressEvent`]s to a callback, e.g., to forward them to a channel (see
+/// [`channel`](CallbackReporter::channel)). An event is emitted when a task starts,
+/// finishes and every time its completed percentage crosses a multiple of the
+/// percent step (by default, every percent).
+pub struct CallbackReporter<'a> {
+    callback: Box<dyn Fn(ProgressEvent) + 'a>,
+    percent_step: u64,
+}
+
+impl Debug for CallbackReporter<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallbackReporter")
+            .field("percent_step", &self.percent_step)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> CallbackReporter<'a> {
+    pub fn new(callback: impl Fn(ProgressEvent) + 'a) -> Self {
+        Self {
+            callback: Box::new(callback),
+            percent_step: 1,
+        }
+    }
+
+    /// Send the [`ProgressEvent`]s to a channel. Events are dropped once the receiver
+    /// is disconnected.
+    pub fn channel(sender: Sender<ProgressEvent>) -> Self {
+        Self::new(move |event| {
+            let _ = sender.send(event);
+        })
+    }
+
+    /// Only emit an advancement every `percent_step` percent.
+    pub fn with_percent_step(mut self, percent_step: u64) -> Self {
+        self.percent_step = percent_step;
+        self
+    }
+}
+
+impl ProgressReporter for CallbackReporter<'_> {
+    fn start(&self, total: u64, message: String) -> Box<dyn Progress + '_> {
+        Box::new(EventProgress::start(
+            Box::new(|event| (self.callback)(event)),
+            total,
+            message,
+            self.percent_step,
+        ))
+    }
+}
+
+/// Emit the progress as `log` records with the target `pcomprs::progress`: When a
+/// task starts, finishes and every 10 percent by default. Applications using `tracing`
+/// can receive them using `tracing-log`.
+#[cfg(feature = "log")]
+#[derive(Debug, Clone, Copy)]
+pub struct LogReporter {
+    level: log::Level,
+    percent_step: u64,
+}
+
+#[cfg(feature = "log")]
+impl Default for LogReporter {
+    fn default() -> Self {
+        Self::new(log::Level::Info)
+    }
+}
+
+#[cfg(feature = "log")]
+impl LogReporter {
+    pub fn new(level: log::Level) -> Self {
+        Self {
+            level,
+            percent_step: 10,
+        }
+    }
+
+    /// Only log an advancement every `percent_step` percent.
+    pub fn with_percent_step(mut self, percent_step: u64) -> Self {
+        self.percent_step = percent_step;
+        self
+    }
+}
+
+#[cfg(feature = "log")]
+impl ProgressReporter for LogReporter {
+    fn start(&self, total: u64, message: String) -> Box<dyn Progress + '_> {
+        Box::new(EventProgress::start(
+            Box::new(|event| {
+                log::log!(
+                    target: "pcomprs::progress",
+                    self.level,
+                    "{} {:?}: {}/{} ({}%)",
+                    event.message,
+                    event.kind,
+                    event.position,
+                    event.total,
+                    event.percent()
+                )
+            }),
+            total,
+            message,
+            self.percent_step,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_channel_reporter() {
+        let (sender, receiver) = channel();
+        let reporter = CallbackReporter::channel(sender).with_percent_step(25);
+        let progress = reporter.start(8, "Task".into());
+        (0..8).for_each(|_| progress.inc(1));
+        progress.finish();
+
+        let events: Vec<(ProgressEventKind, u64)> = receiver
+            .try_iter()
+            .map(|event| (event.kind, event.position))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (ProgressEventKind::Started, 0),
+                (ProgressEventKind::Advanced, 2),
+                (ProgressEventKind::Advanced, 4),
+                (ProgressEventKind::Advanced, 6),
+                (ProgressEventKind::Advanced, 8),
+                (ProgressEventKind::Finished, 8),
+            ]
+        );
+    }
+}